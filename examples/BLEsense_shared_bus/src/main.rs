@@ -0,0 +1,143 @@
+// Example for Arduino 33 BLE Sense with built-in LPS22HB and HTS221 sensors,
+// both connected to I2C1.
+//
+// The bus is kept in a RefCell and shared between the two drivers:
+// the LPS22HB driver owns a SharedI2c handle, while the HTS221 driver
+// is handed a SharedI2c handle for each call.
+// A second, detached LPS22HB driver is lent the bus for each access the same way,
+// to check the device ID and to read the temperature.
+// Both LPS22HB drivers talk to the same physical sensor: a register written through
+// one of them is not seen by the shadow copy of the other, so the shadow copy stays
+// disabled (the default) and only the first driver changes the configuration.
+// Reads pressure, temperature and humidity every second, prints the readings to serial.
+
+#![no_main]
+#![no_std]
+
+use panic_halt as _;
+
+use nrf52840_hal as hal;
+
+use hal::{pac::{CorePeripherals, Peripherals},
+        prelude::*,
+        gpio::Level,
+        delay::Delay,
+        Twim,
+        uarte::{Uarte,Parity,Baudrate},
+        };
+
+use cortex_m_rt::entry;
+
+use arrayvec::ArrayString;
+use core::cell::RefCell;
+use core::fmt;
+use core::fmt::Write;
+
+use lps22hb::interface::{I2cInterface,
+                        SharedI2c,
+                        i2c::I2cAddress};
+use lps22hb::{LPS22HB, ODR};
+
+const BOOT_DELAY_MS: u16 = 100; //small delay for the I2C to initiate correctly and start on boot without having to reset the board
+
+#[entry]
+fn main() -> ! {
+
+    let p = Peripherals::take().unwrap();
+    let core = CorePeripherals::take().unwrap();
+
+    let port0 = hal::gpio::p0::Parts::new(p.P0);
+    let port1 = hal::gpio::p1::Parts::new(p.P1);
+
+    let _vdd_env = port0.p0_22.into_push_pull_output(Level::High); // powers the LPS22HB and HTS221 sensors, as per board schematics
+
+    let _r_pullup = port1.p1_00.into_push_pull_output(Level::High); // necessary for SDA1 and SCL1 to work, as per board schematics
+
+    // set up delay provider
+    let mut delay = Delay::new(core.SYST);
+
+    // define I2C1 pins
+    let scl1 = port0.p0_15.into_floating_input().degrade(); // clock
+    let sda1 = port0.p0_14.into_floating_input().degrade(); // data
+
+    let i2c1_pins = hal::twim::Pins{
+        scl: scl1,
+        sda: sda1
+    };
+
+    // wait for just a moment
+    delay.delay_ms(BOOT_DELAY_MS);
+
+    // set up I2C1, shared by the sensors
+    let i2c1 = RefCell::new(Twim::new(p.TWIM1, i2c1_pins, hal::twim::Frequency::K400));
+
+    // define pins for UART
+    // using A6 for CTS and A7 for RTS
+    let rx_pin = port1.p1_10.into_floating_input().degrade();
+    let tx_pin = port1.p1_03.into_push_pull_output(Level::Low).degrade();
+    let ct_pin = port0.p0_28.into_floating_input().degrade(); // CTS: not used but necessary for configuration, pin may vary
+    let rt_pin = port0.p0_03.into_push_pull_output(Level::Low).degrade(); // RTS: not used but necessary for configuration, pin may vary
+
+    let uart_pins = hal::uarte::Pins{
+            rxd: rx_pin,
+            txd: tx_pin,
+            cts: Some(ct_pin),
+            rts: Some(rt_pin),
+            };
+
+    // set up UART
+    let mut serial = Uarte::new(p.UARTE0, uart_pins, Parity::EXCLUDED, Baudrate::BAUD9600);
+
+    // a driver instance without a bus, lent the bus for each access; it keeps its state in between
+    let mut lps22_detached = LPS22HB::detached(I2cAddress::SA0_GND);
+    let id = lps22_detached
+        .with_bus(&mut *i2c1.borrow_mut(), |lps22| lps22.get_device_id())
+        .unwrap();
+    assert_eq!(id, 0b10110001);
+
+    // the LPS22HB driver used in the loop owns its handle to the shared bus
+    let i2c_interface = I2cInterface::init(SharedI2c::new(&i2c1), I2cAddress::SA0_GND);
+    let mut lps22 = LPS22HB::new(i2c_interface);
+    lps22.bdu_enable(true).unwrap();
+    lps22.set_datarate(ODR::PowerDown).unwrap();
+
+    // the HTS221 driver borrows a handle for each call
+    let mut hts221_bus = SharedI2c::new(&i2c1);
+    let mut hts221 = hts221::Builder::new()
+        .with_default_7bit_address()
+        .with_data_rate(hts221::DataRate::Continuous1Hz)
+        .build(&mut hts221_bus)
+        .unwrap();
+
+    loop {
+
+        lps22.one_shot().unwrap();
+
+        // wait for the conversion to complete
+        loop {
+            let status = lps22.get_data_status().unwrap();
+            if status.press_available && status.temp_available {
+                break;
+            }
+            delay.delay_ms(1_u32);
+        }
+
+        let mut buf = ArrayString::<[u8; 64]>::new();
+
+        let press = lps22.read_pressure().unwrap();
+        let temp = lps22_detached
+            .with_bus(&mut *i2c1.borrow_mut(), |lps22| lps22.read_temperature())
+            .unwrap();
+        let humidity = hts221.humidity_x2(&mut hts221_bus).unwrap() as f32 / 2.0;
+
+        format_reading(&mut buf, press, temp, humidity);
+        serial.write_str(buf.as_str()).unwrap();
+
+        delay.delay_ms(1000_u32);
+    }
+}
+
+/// Simple formatter to pretty print the sensor values
+fn format_reading(buf: &mut ArrayString<[u8; 64]>, press: f32, temp: f32, humidity: f32) {
+    fmt::write(buf, format_args!("P: {:.02}hPA, T: {:.02}C, RH: {:.01}%\r\n", press, temp, humidity)).unwrap();
+}
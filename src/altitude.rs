@@ -0,0 +1,31 @@
+//! Pressure altitude using the international standard atmosphere (ISA)
+
+/// Standard sea-level pressure in hPa
+pub const STANDARD_PRESSURE: f32 = 1013.25;
+
+/// 0 °C in kelvin
+pub(crate) const ZERO_CELSIUS: f32 = 273.15;
+
+/// Exponent of the barometric formula, R * L / (g * M)
+const BAROMETRIC_EXPONENT: f32 = 0.190_263;
+
+/// Height of the troposphere scale, T0 / L, in metres
+const ISA_SCALE_HEIGHT: f32 = 44_330.8;
+
+/// Altitude in metres above the level where the pressure equals `reference` (both in hPa).
+/// Use `STANDARD_PRESSURE` as reference to get the standard pressure altitude.
+pub fn altitude_from_pressure(pressure: f32, reference: f32) -> f32 {
+    ISA_SCALE_HEIGHT * (1.0 - libm::powf(pressure / reference, BAROMETRIC_EXPONENT))
+}
+
+/// Pressure in hPa at `altitude` metres above the level where the pressure equals `reference`.
+/// Inverse of [`altitude_from_pressure`].
+pub fn pressure_at_altitude(altitude: f32, reference: f32) -> f32 {
+    reference * libm::powf(1.0 - altitude / ISA_SCALE_HEIGHT, 1.0 / BAROMETRIC_EXPONENT)
+}
+
+/// Pressure in hPa reduced to sea level from a `pressure` measured at `altitude` metres,
+/// using the standard atmosphere
+pub fn sea_level_pressure(pressure: f32, altitude: f32) -> f32 {
+    pressure / libm::powf(1.0 - altitude / ISA_SCALE_HEIGHT, 1.0 / BAROMETRIC_EXPONENT)
+}
@@ -0,0 +1,246 @@
+//! Software oversampling: statistics over repeated one-shot or FIFO readings
+//!
+//! The LPS22HB has no on-chip averaging, so low-noise readings are obtained
+//! by taking several samples and averaging them in firmware.
+
+use super::*;
+use embedded_hal::blocking::delay::DelayMs;
+use fifo::{FIFOConfig, FIFO_DEPTH};
+use sensor::Measurement;
+
+/// Maximum number of samples that can be averaged in one call (same as the FIFO depth)
+pub const MAX_SAMPLES: usize = FIFO_DEPTH;
+
+/// Maximum time to wait for a one-shot conversion to complete, in ms
+pub(crate) const ONE_SHOT_TIMEOUT_MS: u8 = 50;
+
+/// Errors of the measurements waiting for a conversion
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MeasureError<E> {
+    /// Interface error
+    Interface(E),
+    /// The conversion did not complete in time
+    Timeout,
+    /// The number of samples requested is zero or larger than `MAX_SAMPLES`
+    InvalidSampleCount,
+}
+
+impl<E> From<E> for MeasureError<E> {
+    fn from(error: E) -> Self {
+        MeasureError::Interface(error)
+    }
+}
+
+/// Outlier rejection applied before computing the statistics
+#[derive(Debug, Clone, Copy)]
+pub enum OutlierRejection {
+    /// Use all samples
+    Disabled,
+    /// Discard samples further than the given number of standard deviations from the mean
+    Sigma(f32),
+}
+
+/// Statistics over a set of samples of one channel
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Statistics {
+    /// Mean value
+    pub mean: f32,
+    /// Smallest value
+    pub min: f32,
+    /// Largest value
+    pub max: f32,
+    /// Sample standard deviation
+    pub std_dev: f32,
+    /// Number of samples used (after outlier rejection)
+    pub count: usize,
+}
+
+impl Statistics {
+    /// Compute the statistics of `samples`, optionally rejecting outliers first.
+    /// Returns default (all zero) statistics for an empty slice.
+    pub fn from_samples(samples: &[f32], rejection: OutlierRejection) -> Self {
+        let all = Self::compute(samples.iter().copied());
+        match rejection {
+            OutlierRejection::Sigma(k) if all.count > 2 && all.std_dev > 0.0 => {
+                let limit = k * all.std_dev;
+                let kept = Self::compute(
+                    samples
+                        .iter()
+                        .copied()
+                        .filter(|x| libm::fabsf(x - all.mean) <= limit),
+                );
+                if kept.count > 0 {
+                    kept
+                } else {
+                    all
+                }
+            }
+            _ => all,
+        }
+    }
+
+    fn compute<I: Iterator<Item = f32> + Clone>(samples: I) -> Self {
+        let mut stats = Statistics {
+            min: f32::MAX,
+            max: f32::MIN,
+            ..Default::default()
+        };
+        let mut sum = 0.0;
+        for x in samples.clone() {
+            sum += x;
+            stats.min = stats.min.min(x);
+            stats.max = stats.max.max(x);
+            stats.count += 1;
+        }
+        if stats.count == 0 {
+            return Statistics::default();
+        }
+        stats.mean = sum / stats.count as f32;
+        if stats.count > 1 {
+            let squares: f32 = samples.map(|x| (x - stats.mean) * (x - stats.mean)).sum();
+            stats.std_dev = libm::sqrtf(squares / (stats.count - 1) as f32);
+        }
+        stats
+    }
+}
+
+/// Result of an averaged measurement: statistics for both channels
+#[derive(Debug, Clone, Copy, Default)]
+pub struct AveragedMeasurement {
+    /// Number of samples taken (before outlier rejection)
+    pub samples: usize,
+    /// Pressure statistics, in hPa
+    pub pressure: Statistics,
+    /// Temperature statistics, in degrees Celsius
+    pub temperature: Statistics,
+}
+
+impl AveragedMeasurement {
+    /// Compute the statistics of both channels of a set of samples.
+    /// Returns `None` if `samples` is empty or longer than `MAX_SAMPLES`.
+    pub fn from_measurements(
+        samples: &[Measurement],
+        rejection: OutlierRejection,
+    ) -> Option<Self> {
+        let n = samples.len();
+        if n == 0 || n > MAX_SAMPLES {
+            return None;
+        }
+        let mut pressure = [0.0f32; MAX_SAMPLES];
+        let mut temperature = [0.0f32; MAX_SAMPLES];
+        for (i, sample) in samples.iter().enumerate() {
+            pressure[i] = sample.pressure;
+            temperature[i] = sample.temperature;
+        }
+        Some(AveragedMeasurement {
+            samples: n,
+            pressure: Statistics::from_samples(&pressure[..n], rejection),
+            temperature: Statistics::from_samples(&temperature[..n], rejection),
+        })
+    }
+}
+
+impl<T, E> LPS22HB<T>
+where
+    T: Interface<Error = E>,
+{
+    /// Take `n` one-shot measurements and return their statistics.
+    /// The device is left in power-down mode.
+    /// Fails with `MeasureError::InvalidSampleCount` if `n` is zero or larger than `MAX_SAMPLES`,
+    /// and with `MeasureError::Timeout` if a conversion does not complete within 50 ms.
+    pub fn measure_averaged<D: DelayMs<u8>>(
+        &mut self,
+        n: usize,
+        delay: &mut D,
+        rejection: OutlierRejection,
+    ) -> Result<AveragedMeasurement, MeasureError<E>> {
+        if n == 0 || n > MAX_SAMPLES {
+            return Err(MeasureError::InvalidSampleCount);
+        }
+        let mut samples = [Measurement::default(); MAX_SAMPLES];
+        for sample in samples.iter_mut().take(n) {
+            self.one_shot()?;
+            self.wait_for_data(delay)?;
+            *sample = self.read_measurement()?;
+        }
+        AveragedMeasurement::from_measurements(&samples[..n], rejection)
+            .ok_or(MeasureError::InvalidSampleCount)
+    }
+
+    /// Collect `n` samples in the FIFO at the highest data rate and return their statistics.
+    /// CTRL_REG1, CTRL_REG2 and FIFO_CTRL are restored afterwards, so the previous data rate
+    /// and FIFO mode resume; samples already stored in the FIFO are discarded.
+    /// Fails with `MeasureError::InvalidSampleCount` if `n` is zero or larger than `FIFO_DEPTH`,
+    /// and with `MeasureError::Timeout` if the FIFO does not fill in twice the expected time.
+    pub fn measure_averaged_fifo<D: DelayMs<u8>>(
+        &mut self,
+        n: usize,
+        delay: &mut D,
+        rejection: OutlierRejection,
+    ) -> Result<AveragedMeasurement, MeasureError<E>> {
+        if n == 0 || n > FIFO_DEPTH {
+            return Err(MeasureError::InvalidSampleCount);
+        }
+        let ctrl_reg1 = self.read_register(Registers::CTRL_REG1)?;
+        let ctrl_reg2 = self.read_register(Registers::CTRL_REG2)?;
+        let fifo_ctrl = self.read_register(Registers::FIFO_CTRL)?;
+
+        let mut samples = [Measurement::default(); FIFO_DEPTH];
+        let collected = self.collect_fifo(&mut samples[..n], delay);
+
+        // stop the conversions before restoring the FIFO mode, then resume the previous data rate
+        self.write_register(Registers::CTRL_REG1, ctrl_reg1 & !Bitmasks::ODR_MASK)?;
+        self.write_register(Registers::FIFO_CTRL, fifo_ctrl)?;
+        self.write_register(
+            Registers::CTRL_REG2,
+            ctrl_reg2 & !(Bitmasks::BOOT | Bitmasks::SWRESET | Bitmasks::ONE_SHOT),
+        )?;
+        self.write_register(Registers::CTRL_REG1, ctrl_reg1)?;
+
+        collected?;
+        AveragedMeasurement::from_measurements(&samples[..n], rejection)
+            .ok_or(MeasureError::InvalidSampleCount)
+    }
+
+    /// Fill `samples` from the FIFO, running at 75 Hz
+    fn collect_fifo<D: DelayMs<u8>>(
+        &mut self,
+        samples: &mut [Measurement],
+        delay: &mut D,
+    ) -> Result<(), MeasureError<E>> {
+        let n = samples.len();
+
+        // going through Bypass mode empties the FIFO
+        self.configure_fifo(FIFO_ON::Enabled, FIFOConfig::default())?;
+        self.configure_fifo(
+            FIFO_ON::Enabled,
+            FIFOConfig {
+                fifo_mode: FIFO_MODE::FIFO,
+                ..Default::default()
+            },
+        )?;
+        self.set_datarate(ODR::_75Hz)?;
+
+        // one sample every 13.3 ms at 75 Hz, allow twice that
+        for _ in 0..(n * 27) {
+            if self.get_fifo_status()?.fifo_level as usize >= n {
+                self.read_fifo(samples)?;
+                return Ok(());
+            }
+            delay.delay_ms(1);
+        }
+        Err(MeasureError::Timeout)
+    }
+
+    /// Poll the data available flags until both pressure and temperature are ready
+    fn wait_for_data<D: DelayMs<u8>>(&mut self, delay: &mut D) -> Result<(), MeasureError<E>> {
+        for _ in 0..ONE_SHOT_TIMEOUT_MS {
+            let status = self.get_data_status()?;
+            if status.press_available && status.temp_available {
+                return Ok(());
+            }
+            delay.delay_ms(1);
+        }
+        Err(MeasureError::Timeout)
+    }
+}
@@ -4,21 +4,10 @@
 
 use super::*;
 
-impl<T, E> LPS22HB<T>
+impl<T, E, MODE> LPS22HB<T, MODE>
 where
     T: Interface<Error = E>,
 {
-    /// Set output data rate        
-    pub fn set_datarate(&mut self, odr: ODR) -> Result<(), T::Error> {
-        let mut reg_data = [0u8];
-        self.interface
-            .read(Registers::CTRL_REG1.addr(), &mut reg_data)?;
-        let mut payload = reg_data[0];
-        payload &= !Bitmasks::ODR_MASK;
-        payload |= odr.value();
-        self.interface.write(Registers::CTRL_REG1.addr(), payload)?;
-        Ok(())
-    }
 
     /// Enable or disable block data update
     pub fn bdu_enable(&mut self, flag: bool) -> Result<(), T::Error> {
@@ -28,6 +17,137 @@ where
         }
     }
 
+    /// Disables I2C interface (default 0, I2C enabled)
+    pub fn i2c_disable(&mut self, flag: bool) -> Result<(), T::Error> {
+        match flag {
+            true => self.set_register_bit_flag(Registers::CTRL_REG2, Bitmasks::I2C_DIS),
+            false => self.clear_register_bit_flag(Registers::CTRL_REG2, Bitmasks::I2C_DIS),
+        }
+    }
+
+    /// Sets SPI Mode (default 4-wire)
+    pub fn spi_config(&mut self, mode: SPI_Mode) -> Result<(), T::Error> {
+        match mode {
+            SPI_Mode::_3wire => self.set_register_bit_flag(Registers::CTRL_REG1, Bitmasks::SIM),
+            SPI_Mode::_4wire => self.clear_register_bit_flag(Registers::CTRL_REG1, Bitmasks::SIM),
+        }
+    }
+
+    /// Register address automatically incremented during a multiple byte access with a serial interface (I2C or SPI).
+    /// Default value: enabled
+    pub fn address_incrementing(&mut self, flag: bool) -> Result<(), T::Error> {
+        match flag {
+            true => self.set_register_bit_flag(Registers::CTRL_REG2, Bitmasks::IF_ADD_INC),
+            false => self.clear_register_bit_flag(Registers::CTRL_REG2, Bitmasks::IF_ADD_INC),
+        }
+    }
+
+    /// Is reboot phase running?
+    pub fn reboot_running(&mut self) -> Result<bool, T::Error> {
+        self.is_register_bit_flag_high(Registers::INT_SOURCE, Bitmasks::BOOT_STATUS)
+    }
+
+    /// Get the current power mode
+    pub fn get_power_mode(&mut self) -> Result<PowerMode, T::Error> {
+        match self.is_register_bit_flag_high(Registers::RES_CONF, Bitmasks::LC_EN)? {
+            true => Ok(PowerMode::LowCurrent),
+            false => Ok(PowerMode::LowNoise),
+        }
+    }
+
+    /// Get the output data rate, `None` for a reserved value
+    pub fn get_datarate(&mut self) -> Result<Option<ODR>, T::Error> {
+        let ctrl_reg1 = self.read_register(Registers::CTRL_REG1)?;
+        Ok(ODR::try_from(ctrl_reg1).ok())
+    }
+
+    /// Get the low-pass filter setting
+    pub fn get_lowpass_filter(&mut self) -> Result<LowPassFilter, T::Error> {
+        let ctrl_reg1 = self.read_register(Registers::CTRL_REG1)?;
+        Ok(LowPassFilter::from_value(ctrl_reg1))
+    }
+
+    /// Cut-off frequency in Hz of the pressure data with the current ODR and low-pass filter setting,
+    /// `None` in power-down mode
+    pub fn get_lowpass_cutoff(&mut self) -> Result<Option<f32>, T::Error> {
+        let ctrl_reg1 = self.read_register(Registers::CTRL_REG1)?;
+        let filter = LowPassFilter::from_value(ctrl_reg1);
+        Ok(ODR::try_from(ctrl_reg1).ok().and_then(|odr| filter.cutoff_hz(odr)))
+    }
+
+    // Implementations shared by the untyped and the typed API
+    pub(crate) fn write_datarate(&mut self, odr: ODR) -> Result<(), T::Error> {
+        let mut payload = self.read_register(Registers::CTRL_REG1)?;
+        payload &= !Bitmasks::ODR_MASK;
+        payload |= odr.value();
+        self.write_register(Registers::CTRL_REG1, payload)?;
+        Ok(())
+    }
+
+    pub(crate) fn write_reboot(&mut self) -> Result<(), T::Error> {
+        self.set_register_bit_flag(Registers::CTRL_REG2, Bitmasks::BOOT)
+    }
+
+    pub(crate) fn write_power_mode(&mut self, mode: PowerMode) -> Result<(), T::Error> {
+        let ctrl_reg1 = self.read_register(Registers::CTRL_REG1)?;
+        let running = ctrl_reg1 & Bitmasks::ODR_MASK != 0;
+        if running {
+            self.write_register(Registers::CTRL_REG1, ctrl_reg1 & !Bitmasks::ODR_MASK)?;
+        }
+        match mode {
+            PowerMode::LowNoise => self.clear_register_bit_flag(Registers::RES_CONF, Bitmasks::LC_EN),
+            PowerMode::LowCurrent => self.set_register_bit_flag(Registers::RES_CONF, Bitmasks::LC_EN),
+        }?;
+        if running {
+            self.write_register(Registers::CTRL_REG1, ctrl_reg1)?;
+        }
+        Ok(())
+    }
+
+    pub(crate) fn write_software_reset(&mut self) -> Result<(), T::Error> {
+        self.set_register_bit_flag(Registers::CTRL_REG2, Bitmasks::SWRESET)
+    }
+
+    pub(crate) fn write_low_power(&mut self) -> Result<(), T::Error> {
+        self.set_register_bit_flag(Registers::RES_CONF, Bitmasks::LC_EN)
+    }
+
+    pub(crate) fn write_lowpass_filter(&mut self, enable: bool, configure: bool) -> Result<(), T::Error> {
+        let mut payload = self.read_register(Registers::CTRL_REG1)? & !(Bitmasks::EN_LPFP | Bitmasks::LPFP_CFG);
+        if enable {
+            payload |= Bitmasks::EN_LPFP;
+        }
+        if configure {
+            payload |= Bitmasks::LPFP_CFG;
+        }
+        self.write_register(Registers::CTRL_REG1, payload)
+    }
+
+    pub(crate) fn write_lowpass_setting(&mut self, filter: LowPassFilter, reset: bool) -> Result<(), T::Error> {
+        let mut payload = self.read_register(Registers::CTRL_REG1)?;
+        payload &= !(Bitmasks::EN_LPFP | Bitmasks::LPFP_CFG);
+        payload |= filter.value();
+        self.write_register(Registers::CTRL_REG1, payload)?;
+        if reset {
+            self.read_lowpass_filter_reset()?;
+        }
+        Ok(())
+    }
+
+    pub(crate) fn read_lowpass_filter_reset(&mut self) -> Result<(), T::Error> {
+        let mut _data = [0u8; 1];
+        self.interface
+            .read(Registers::LPFP_RES.addr(), &mut _data)?;
+        Ok(())
+    }
+}
+
+/// Functions changing the reference pressure, not available during a typed one-shot measurement
+impl<T, E, MODE> LPS22HB<T, MODE>
+where
+    T: Interface<Error = E>,
+    MODE: Configurable,
+{
     /// AUTOZERO: when set to ‘1’, the measured pressure is used
     /// as the reference in REF_P (0x15, 0x16, 0x17).
     /// From that point on the output pressure registers are updated and the same value
@@ -48,29 +168,30 @@ where
         self.set_register_bit_flag(Registers::INTERRUPT_CFG, Bitmasks::RESET_AZ)
     }
 
-    /// Disables I2C interface (default 0, I2C enabled)
-    pub fn i2c_disable(&mut self, flag: bool) -> Result<(), T::Error> {
+    /// AUTORIFP: when set to ‘1’, the measured pressure is used as the reference in REF_P
+    /// for interrupt generation only. The output pressure registers keep the measured pressure.
+    /// P_DIFF_IN = measured pressure - REF_P
+    pub fn autorifp_config(&mut self, flag: bool) -> Result<(), T::Error> {
         match flag {
-            true => self.set_register_bit_flag(Registers::CTRL_REG2, Bitmasks::I2C_DIS),
-            false => self.clear_register_bit_flag(Registers::CTRL_REG2, Bitmasks::I2C_DIS),
+            true => self.set_register_bit_flag(Registers::INTERRUPT_CFG, Bitmasks::AUTORIFP),
+            false => self.clear_register_bit_flag(Registers::INTERRUPT_CFG, Bitmasks::AUTORIFP),
         }
     }
 
-    /// Sets SPI Mode (default 4-wire)
-    pub fn spi_config(&mut self, mode: SPI_Mode) -> Result<(), T::Error> {
-        match mode {
-            SPI_Mode::_3wire => self.set_register_bit_flag(Registers::CTRL_REG1, Bitmasks::SIM),
-            SPI_Mode::_4wire => self.clear_register_bit_flag(Registers::CTRL_REG1, Bitmasks::SIM),
-        }
+    /// Resets the AUTORIFP function. Self-cleared.
+    pub fn autorifp_reset(&mut self) -> Result<(), T::Error> {
+        self.set_register_bit_flag(Registers::INTERRUPT_CFG, Bitmasks::RESET_ARP)
     }
+}
 
-    /// Register address automatically incremented during a multiple byte access with a serial interface (I2C or SPI).
-    /// Default value: enabled
-    pub fn address_incrementing(&mut self, flag: bool) -> Result<(), T::Error> {
-        match flag {
-            true => self.set_register_bit_flag(Registers::CTRL_REG2, Bitmasks::IF_ADD_INC),
-            false => self.clear_register_bit_flag(Registers::CTRL_REG2, Bitmasks::IF_ADD_INC),
-        }
+/// Functions depending on the power mode, see the `typestate` module for the typed API
+impl<T, E> LPS22HB<T>
+where
+    T: Interface<Error = E>,
+{
+    /// Set output data rate        
+    pub fn set_datarate(&mut self, odr: ODR) -> Result<(), T::Error> {
+        self.write_datarate(odr)
     }
 
     /// Reboot. Refreshes the content of the internal registers stored in the Flash memory block.
@@ -81,46 +202,40 @@ where
     /// At the end of the boot process the BOOT bit is set again to ‘0’ by hardware.
     /// The BOOT bit takes effect after one ODR clock cycle.
     pub fn reboot(&mut self) -> Result<(), T::Error> {
-        self.set_register_bit_flag(Registers::CTRL_REG2, Bitmasks::BOOT)
+        self.write_reboot()
     }
 
-    /// Is reboot phase running?
-    pub fn reboot_running(&mut self) -> Result<bool, T::Error> {
-        self.is_register_bit_flag_high(Registers::INT_SOURCE, Bitmasks::BOOT_STATUS)
+    /// Set the power mode (low-noise or low-current).
+    /// The device is put in power-down mode while LC_EN is changed, then the previous ODR is restored.
+    pub fn set_power_mode(&mut self, mode: PowerMode) -> Result<(), T::Error> {
+        self.write_power_mode(mode)
     }
 
     /// Run software reset (resets the device to the power-on configuration, takes 4 usec)
     pub fn software_reset(&mut self) -> Result<(), T::Error> {
-        self.set_register_bit_flag(Registers::CTRL_REG2, Bitmasks::SWRESET)
+        self.write_software_reset()
     }
 
-    // SWITCHING INTO POWER-DOWN COULD BE ADDED TO THIS FUNCTION
-    /// Enable low-power mode (must be done only with the device in power-down mode)
+    /// Enable low-power mode (must be done only with the device in power-down mode,
+    /// see `set_power_mode` for switching in any mode)
     pub fn enable_low_power(&mut self) -> Result<(), T::Error> {
-        self.set_register_bit_flag(Registers::RES_CONF, Bitmasks::LC_EN)
+        self.write_low_power()
     }
 
-    // LOWPASS FILTER ENABLING AND CONFIGURING COULD BE MOVED TOGETHER
+    /// Set the low-pass filter on pressure data in Continuous mode,
+    /// optionally resetting it to avoid the transitory phase
+    pub fn set_lowpass_filter(&mut self, filter: LowPassFilter, reset: bool) -> Result<(), T::Error> {
+        self.write_lowpass_setting(filter, reset)
+    }
 
     /// Enable and configure low-pass filter on pressure data in Continuous mode
     pub fn lowpass_filter(&mut self, enable: bool, configure: bool) -> Result<(), T::Error> {
-        match enable {
-            true => self.set_register_bit_flag(Registers::CTRL_REG1, Bitmasks::EN_LPFP),
-            false => self.clear_register_bit_flag(Registers::CTRL_REG1, Bitmasks::EN_LPFP),
-        }?;
-        match configure {
-            true => self.set_register_bit_flag(Registers::CTRL_REG1, Bitmasks::LPFP_CFG),
-            false => self.clear_register_bit_flag(Registers::CTRL_REG1, Bitmasks::LPFP_CFG),
-        }?;
-        Ok(())
+        self.write_lowpass_filter(enable, configure)
     }
-   
+
     /// Reset low-pass filter.  If the LPFP is active, in order to avoid the transitory phase,
     /// the filter can be reset by reading this register before generating pressure measurements.
     pub fn lowpass_filter_reset(&mut self) -> Result<(), T::Error> {
-        let mut _data = [0u8; 1];
-        self.interface
-            .read(Registers::LPFP_RES.addr(), &mut _data)?;
-        Ok(())
+        self.read_lowpass_filter_reset()
     }
 }
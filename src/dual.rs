@@ -0,0 +1,146 @@
+//! Two sensors on one bus for differential pressure measurement
+//!
+//! With one sensor at `I2cAddress::SA0_GND` and the other at `I2cAddress::SA0_VCC`,
+//! both can share an I2C bus (e.g. through bus proxies). The wrapper configures
+//! them identically, triggers synchronized one-shots or streams both at the
+//! same data rate, and returns the pressure difference (first minus second).
+//! The static offset between the two parts can be calibrated out and
+//! programmed into the RPDS register of the second sensor.
+
+use super::*;
+use average::{MeasureError, ONE_SHOT_TIMEOUT_MS};
+use embedded_hal::blocking::delay::DelayMs;
+use sensor::Measurement;
+
+/// Pair of sensors measuring a pressure difference
+pub struct DualLps22hb<T> {
+    first: LPS22HB<T>,
+    second: LPS22HB<T>,
+}
+
+/// Simultaneous readings of both sensors
+#[derive(Debug, Clone, Copy, Default)]
+pub struct DualMeasurement {
+    /// Reading of the first sensor
+    pub first: Measurement,
+    /// Reading of the second sensor
+    pub second: Measurement,
+}
+
+impl DualMeasurement {
+    /// Pressure difference, first minus second, in hPa
+    pub fn difference(&self) -> f32 {
+        self.first.pressure - self.second.pressure
+    }
+}
+
+impl<T, E> DualLps22hb<T>
+where
+    T: Interface<Error = E>,
+{
+    /// Combine two drivers
+    pub fn new(first: LPS22HB<T>, second: LPS22HB<T>) -> Self {
+        DualLps22hb { first, second }
+    }
+
+    /// Destroy the wrapper, return both drivers
+    pub fn destroy(self) -> (LPS22HB<T>, LPS22HB<T>) {
+        (self.first, self.second)
+    }
+
+    /// Access both drivers
+    pub fn sensors(&mut self) -> (&mut LPS22HB<T>, &mut LPS22HB<T>) {
+        (&mut self.first, &mut self.second)
+    }
+
+    /// Apply the same configuration to both sensors, e.g.
+    /// `dual.configure(|s| s.bdu_enable(true))`
+    pub fn configure<F>(&mut self, mut f: F) -> Result<(), E>
+    where
+        F: FnMut(&mut LPS22HB<T>) -> Result<(), E>,
+    {
+        f(&mut self.first)?;
+        f(&mut self.second)
+    }
+
+    /// Set the same output data rate on both sensors (`ODR::PowerDown` stops streaming)
+    pub fn set_datarate(&mut self, odr: ODR) -> Result<(), E> {
+        self.configure(|sensor| sensor.set_datarate(odr))
+    }
+
+    /// Trigger a one-shot acquisition on both sensors, back to back
+    pub fn one_shot(&mut self) -> Result<(), E> {
+        self.configure(|sensor| sensor.one_shot())
+    }
+
+    /// Read the latest pressure and temperature of both sensors
+    pub fn read_measurements(&mut self) -> Result<DualMeasurement, E> {
+        Ok(DualMeasurement {
+            first: self.first.read_measurement()?,
+            second: self.second.read_measurement()?,
+        })
+    }
+
+    /// Read the latest pressure difference (first minus second), in hPa
+    pub fn read_difference(&mut self) -> Result<f32, E> {
+        Ok(self.read_measurements()?.difference())
+    }
+
+    /// Trigger synchronized one-shots, wait for both conversions and read the results.
+    /// Unread samples are discarded when the one-shots start, so both readings are fresh.
+    /// Fails with `MeasureError::Timeout` if a conversion does not complete within 50 ms.
+    pub fn measure<D: DelayMs<u8>>(
+        &mut self,
+        delay: &mut D,
+    ) -> Result<DualMeasurement, MeasureError<E>> {
+        self.one_shot()?;
+        let (mut first_ready, mut second_ready) = (false, false);
+        for _ in 0..ONE_SHOT_TIMEOUT_MS {
+            first_ready = first_ready || Self::data_ready(&mut self.first)?;
+            second_ready = second_ready || Self::data_ready(&mut self.second)?;
+            if first_ready && second_ready {
+                return Ok(self.read_measurements()?);
+            }
+            delay.delay_ms(1);
+        }
+        Err(MeasureError::Timeout)
+    }
+
+    /// Measure the static offset between the two sensors over `n` synchronized one-shots,
+    /// exposed to the same pressure, and program it into the RPDS register of the second one.
+    /// Returns the offset in hPa.
+    /// Fails with `MeasureError::InvalidSampleCount` if `n` is zero; if a measurement fails,
+    /// the previous offset of the second sensor is restored.
+    pub fn calibrate_offset<D: DelayMs<u8>>(
+        &mut self,
+        n: u16,
+        delay: &mut D,
+    ) -> Result<f32, MeasureError<E>> {
+        if n == 0 {
+            return Err(MeasureError::InvalidSampleCount);
+        }
+        let previous = self.second.read_pressure_offset()?;
+        self.second.set_pressure_offset_raw(0)?;
+        let mut sum = 0.0;
+        for _ in 0..n {
+            match self.measure(delay) {
+                Ok(measurement) => sum += measurement.difference(),
+                Err(error) => {
+                    self.second.set_pressure_offset_raw(previous)?;
+                    return Err(error);
+                }
+            }
+        }
+        // the second sensor reads higher by -difference, RPDS is subtracted from its output
+        let offset = -sum / n as f32;
+        let raw = libm::roundf(offset * OFFSET_SCALE) as i16;
+        self.second.set_pressure_offset_raw(raw)?;
+        Ok(raw as f32 / OFFSET_SCALE)
+    }
+
+    /// Both the pressure and the temperature of a sensor have been converted
+    fn data_ready(sensor: &mut LPS22HB<T>) -> Result<bool, E> {
+        let status = sensor.get_data_status()?;
+        Ok(status.press_available && status.temp_available)
+    }
+}
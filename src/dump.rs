@@ -0,0 +1,375 @@
+//! Register dump and diagnostic report
+//!
+//! `dump_config_registers` reads the registers with one burst read per block of
+//! contiguous addresses (one read per register with IF_ADD_INC cleared),
+//! bypassing the shadow copy, and leaves the device as it is: INT_SOURCE and
+//! the output registers are skipped, and so is LPFP_RES, as reading it resets the
+//! low-pass filter.
+//!
+//! `dump_registers_destructive` also reads INT_SOURCE and the output registers,
+//! with their read side effects: the latched interrupts are cleared, the data
+//! available flags too and, with the FIFO enabled, the oldest sample is taken out.
+//!
+//! The `Display` implementation (and `defmt::Format` with the `defmt` feature)
+//! decodes the fields on one line:
+//!
+//! ```text
+//! WHO_AM_I=0xb1 ODR=25Hz LPF=ODR/20 BDU=1 SIM=0 LC_EN=0 FIFO=Stream FIFO_EN=1 WTM=31 STOP_ON_FTH=0 ...
+//! ```
+
+use super::*;
+use core::fmt;
+
+/// First address covered by the dump
+const FIRST: u8 = 0x0B;
+/// Blocks of contiguous registers read without side effects: first register and number of registers
+const BLOCKS: [(Registers, usize); 4] = [
+    (Registers::INTERRUPT_CFG, 3), // INTERRUPT_CFG..THS_P_H
+    (Registers::WHO_AM_I, 4),      // WHO_AM_I..CTRL_REG3
+    (Registers::FIFO_CTRL, 7),     // FIFO_CTRL..RES_CONF
+    (Registers::FIFO_STATUS, 2),   // FIFO_STATUS..STATUS
+];
+/// Blocks read by the destructive dump, covering INT_SOURCE and the output registers as well
+const DESTRUCTIVE_BLOCKS: [(Registers, usize); 4] = [
+    (Registers::INTERRUPT_CFG, 3), // INTERRUPT_CFG..THS_P_H
+    (Registers::WHO_AM_I, 4),      // WHO_AM_I..CTRL_REG3
+    (Registers::FIFO_CTRL, 7),     // FIFO_CTRL..RES_CONF
+    (Registers::INT_SOURCE, 8),    // INT_SOURCE..TEMP_OUT_H
+];
+
+/// INT_SOURCE and the output registers are cleared or consumed when read
+fn is_destructive(register: Registers) -> bool {
+    matches!(
+        register,
+        Registers::INT_SOURCE
+            | Registers::PRESS_OUT_XL
+            | Registers::PRESS_OUT_L
+            | Registers::PRESS_OUT_H
+            | Registers::TEMP_OUT_L
+            | Registers::TEMP_OUT_H
+    )
+}
+
+/// Values of the registers (all but LPFP_RES, and without INT_SOURCE and the
+/// output registers unless read by `dump_registers_destructive`)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RegisterDump {
+    bytes: [u8; 0x2D - FIRST as usize],
+    destructive: bool,
+}
+
+impl RegisterDump {
+    /// Value of the register, `None` for the registers that were not read
+    pub fn get(&self, register: Registers) -> Option<u8> {
+        match register {
+            Registers::LPFP_RES => None,
+            _ if is_destructive(register) && !self.destructive => None,
+            _ => Some(self.value(register)),
+        }
+    }
+
+    /// All the registers read and their values, in address order
+    pub fn iter(&self) -> impl Iterator<Item = (Registers, u8)> + '_ {
+        self.blocks().iter().flat_map(move |&(first, len)| {
+            (first.addr()..first.addr() + len as u8).filter_map(move |addr| {
+                Registers::from_addr(addr).map(|register| (register, self.value(register)))
+            })
+        })
+    }
+
+    fn blocks(&self) -> &'static [(Registers, usize)] {
+        if self.destructive {
+            &DESTRUCTIVE_BLOCKS
+        } else {
+            &BLOCKS
+        }
+    }
+
+    fn value(&self, register: Registers) -> u8 {
+        self.bytes[(register.addr() - FIRST) as usize]
+    }
+
+    fn flag(&self, register: Registers, bitmask: u8) -> u8 {
+        (self.value(register) & bitmask != 0) as u8
+    }
+
+    fn le16(&self, low: Registers) -> i16 {
+        let b = &self.bytes[(low.addr() - FIRST) as usize..];
+        (b[1] as i16) << 8 | (b[0] as i16)
+    }
+
+    fn le24(&self, low: Registers) -> i32 {
+        let b = &self.bytes[(low.addr() - FIRST) as usize..];
+        (b[2] as i8 as i32) << 16 | (b[1] as i32) << 8 | (b[0] as i32)
+    }
+
+    /// Output data rate, `Err` for a reserved value
+    pub fn odr(&self) -> Result<ODR, InvalidFieldValue> {
+        ODR::try_from(self.value(Registers::CTRL_REG1))
+    }
+
+    /// Low-pass filter setting
+    pub fn lowpass_filter(&self) -> LowPassFilter {
+        LowPassFilter::from_value(self.value(Registers::CTRL_REG1))
+    }
+
+    /// Power mode
+    pub fn power_mode(&self) -> PowerMode {
+        match self.flag(Registers::RES_CONF, Bitmasks::LC_EN) {
+            0 => PowerMode::LowNoise,
+            _ => PowerMode::LowCurrent,
+        }
+    }
+
+    /// FIFO mode, `Err` for a reserved value
+    pub fn fifo_mode(&self) -> Result<FIFO_MODE, InvalidFieldValue> {
+        FIFO_MODE::try_from(self.value(Registers::FIFO_CTRL))
+    }
+
+    /// INT_DRDY pin configuration
+    pub fn int_drdy(&self) -> Result<INT_DRDY, InvalidFieldValue> {
+        INT_DRDY::try_from(self.value(Registers::CTRL_REG3))
+    }
+
+    /// Interrupt threshold (hPa)
+    pub fn threshold(&self) -> f32 {
+        self.le16(Registers::THS_P_L) as u16 as f32 / OFFSET_SCALE
+    }
+
+    /// Pressure offset (hPa)
+    pub fn pressure_offset(&self) -> f32 {
+        self.le16(Registers::RPDS_L) as f32 / OFFSET_SCALE
+    }
+
+    /// Reference pressure (hPa)
+    pub fn reference_pressure(&self) -> f32 {
+        self.le24(Registers::REF_P_XL) as f32 / PRESS_SCALE
+    }
+
+    /// Pressure in the output registers (hPa), `None` if they were not read
+    pub fn pressure(&self) -> Option<f32> {
+        self.get(Registers::PRESS_OUT_XL)
+            .map(|_| self.le24(Registers::PRESS_OUT_XL) as f32 / PRESS_SCALE)
+    }
+
+    /// Temperature in the output registers (degrees Celsius), `None` if they were not read
+    pub fn temperature(&self) -> Option<f32> {
+        self.get(Registers::TEMP_OUT_L)
+            .map(|_| self.le16(Registers::TEMP_OUT_L) as f32 / TEMP_SCALE)
+    }
+
+    fn odr_name(&self) -> &'static str {
+        match self.odr() {
+            Ok(ODR::PowerDown) => "PowerDown",
+            Ok(ODR::_1Hz) => "1Hz",
+            Ok(ODR::_10Hz) => "10Hz",
+            Ok(ODR::_25Hz) => "25Hz",
+            Ok(ODR::_50Hz) => "50Hz",
+            Ok(ODR::_75Hz) => "75Hz",
+            Err(_) => "reserved",
+        }
+    }
+
+    fn lowpass_filter_name(&self) -> &'static str {
+        match self.lowpass_filter() {
+            LowPassFilter::Disabled => "off",
+            LowPassFilter::OdrDiv9 => "ODR/9",
+            LowPassFilter::OdrDiv20 => "ODR/20",
+        }
+    }
+
+    fn fifo_mode_name(&self) -> &'static str {
+        match self.fifo_mode() {
+            Ok(FIFO_MODE::Bypass) => "Bypass",
+            Ok(FIFO_MODE::FIFO) => "FIFO",
+            Ok(FIFO_MODE::Stream) => "Stream",
+            Ok(FIFO_MODE::Stream_to_FIFO) => "StreamToFIFO",
+            Ok(FIFO_MODE::Bypass_to_stream) => "BypassToStream",
+            Ok(FIFO_MODE::Dynamic_Stream) => "DynamicStream",
+            Ok(FIFO_MODE::Bypass_to_FIFO) => "BypassToFIFO",
+            Err(_) => "reserved",
+        }
+    }
+
+    fn int_drdy_name(&self) -> &'static str {
+        match self.int_drdy() {
+            Ok(INT_DRDY::DataSignal) => "Data",
+            Ok(INT_DRDY::P_high) => "PH",
+            Ok(INT_DRDY::P_low) => "PL",
+            Ok(INT_DRDY::P_low_or_high) => "PH|PL",
+            Err(_) => "reserved",
+        }
+    }
+}
+
+impl fmt::Display for RegisterDump {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        use Registers::*;
+        write!(
+            f,
+            "WHO_AM_I={:#04x} ODR={} LPF={} BDU={} SIM={} LC_EN={} FIFO={} FIFO_EN={} WTM={} STOP_ON_FTH={} IF_ADD_INC={} I2C_DIS={} ",
+            self.value(WHO_AM_I),
+            self.odr_name(),
+            self.lowpass_filter_name(),
+            self.flag(CTRL_REG1, Bitmasks::BDU),
+            self.flag(CTRL_REG1, Bitmasks::SIM),
+            self.flag(RES_CONF, Bitmasks::LC_EN),
+            self.fifo_mode_name(),
+            self.flag(CTRL_REG2, Bitmasks::FIFO_EN),
+            self.value(FIFO_CTRL) & Bitmasks::WTM_MASK,
+            self.flag(CTRL_REG2, Bitmasks::STOP_ON_FTH),
+            self.flag(CTRL_REG2, Bitmasks::IF_ADD_INC),
+            self.flag(CTRL_REG2, Bitmasks::I2C_DIS),
+        )?;
+        write!(
+            f,
+            "INT_S={} INT_H_L={} PP_OD={} DRDY={} F_OVR={} F_FTH={} F_FSS5={} DIFF_EN={} PHE={} PLE={} LIR={} AUTOZERO={} AUTORIFP={} ",
+            self.int_drdy_name(),
+            self.flag(CTRL_REG3, Bitmasks::INT_H_L),
+            self.flag(CTRL_REG3, Bitmasks::PP_OD),
+            self.flag(CTRL_REG3, Bitmasks::DRDY),
+            self.flag(CTRL_REG3, Bitmasks::F_OVR),
+            self.flag(CTRL_REG3, Bitmasks::F_FTH),
+            self.flag(CTRL_REG3, Bitmasks::F_FSS5),
+            self.flag(INTERRUPT_CFG, Bitmasks::DIFF_EN),
+            self.flag(INTERRUPT_CFG, Bitmasks::PHE),
+            self.flag(INTERRUPT_CFG, Bitmasks::PLE),
+            self.flag(INTERRUPT_CFG, Bitmasks::LIR),
+            self.flag(INTERRUPT_CFG, Bitmasks::AUTOZERO),
+            self.flag(INTERRUPT_CFG, Bitmasks::AUTORIFP),
+        )?;
+        write!(
+            f,
+            "THS={:.4}hPa RPDS={:.4}hPa REF={:.4}hPa ",
+            self.threshold(),
+            self.pressure_offset(),
+            self.reference_pressure(),
+        )?;
+        if self.destructive {
+            write!(
+                f,
+                "BOOT_STATUS={} IA={} PH={} PL={} ",
+                self.flag(INT_SOURCE, Bitmasks::BOOT_STATUS),
+                self.flag(INT_SOURCE, Bitmasks::IA),
+                self.flag(INT_SOURCE, Bitmasks::PH),
+                self.flag(INT_SOURCE, Bitmasks::PL),
+            )?;
+        }
+        write!(
+            f,
+            "FSS={} FTH_FIFO={} OVR={} P_DA={} T_DA={} P_OR={} T_OR={}",
+            self.value(FIFO_STATUS) & Bitmasks::FSS_MASK,
+            self.flag(FIFO_STATUS, Bitmasks::FTH_FIFO),
+            self.flag(FIFO_STATUS, Bitmasks::OVR),
+            self.flag(STATUS, Bitmasks::P_DA),
+            self.flag(STATUS, Bitmasks::T_DA),
+            self.flag(STATUS, Bitmasks::P_OR),
+            self.flag(STATUS, Bitmasks::T_OR),
+        )?;
+        if let (Some(pressure), Some(temperature)) = (self.pressure(), self.temperature()) {
+            write!(f, " P={:.4}hPa T={:.2}C", pressure, temperature)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(feature = "defmt")]
+impl defmt::Format for RegisterDump {
+    fn format(&self, f: defmt::Formatter) {
+        use Registers::*;
+        defmt::write!(
+            f,
+            "WHO_AM_I={=u8:#04x} ODR={=str} LPF={=str} BDU={=u8} SIM={=u8} LC_EN={=u8} FIFO={=str} FIFO_EN={=u8} WTM={=u8} STOP_ON_FTH={=u8} IF_ADD_INC={=u8} I2C_DIS={=u8} ",
+            self.value(WHO_AM_I),
+            self.odr_name(),
+            self.lowpass_filter_name(),
+            self.flag(CTRL_REG1, Bitmasks::BDU),
+            self.flag(CTRL_REG1, Bitmasks::SIM),
+            self.flag(RES_CONF, Bitmasks::LC_EN),
+            self.fifo_mode_name(),
+            self.flag(CTRL_REG2, Bitmasks::FIFO_EN),
+            self.value(FIFO_CTRL) & Bitmasks::WTM_MASK,
+            self.flag(CTRL_REG2, Bitmasks::STOP_ON_FTH),
+            self.flag(CTRL_REG2, Bitmasks::IF_ADD_INC),
+            self.flag(CTRL_REG2, Bitmasks::I2C_DIS),
+        );
+        defmt::write!(
+            f,
+            "INT_S={=str} INT_H_L={=u8} PP_OD={=u8} DRDY={=u8} F_OVR={=u8} F_FTH={=u8} F_FSS5={=u8} DIFF_EN={=u8} PHE={=u8} PLE={=u8} LIR={=u8} AUTOZERO={=u8} AUTORIFP={=u8} ",
+            self.int_drdy_name(),
+            self.flag(CTRL_REG3, Bitmasks::INT_H_L),
+            self.flag(CTRL_REG3, Bitmasks::PP_OD),
+            self.flag(CTRL_REG3, Bitmasks::DRDY),
+            self.flag(CTRL_REG3, Bitmasks::F_OVR),
+            self.flag(CTRL_REG3, Bitmasks::F_FTH),
+            self.flag(CTRL_REG3, Bitmasks::F_FSS5),
+            self.flag(INTERRUPT_CFG, Bitmasks::DIFF_EN),
+            self.flag(INTERRUPT_CFG, Bitmasks::PHE),
+            self.flag(INTERRUPT_CFG, Bitmasks::PLE),
+            self.flag(INTERRUPT_CFG, Bitmasks::LIR),
+            self.flag(INTERRUPT_CFG, Bitmasks::AUTOZERO),
+            self.flag(INTERRUPT_CFG, Bitmasks::AUTORIFP),
+        );
+        defmt::write!(
+            f,
+            "THS={=f32}hPa RPDS={=f32}hPa REF={=f32}hPa ",
+            self.threshold(),
+            self.pressure_offset(),
+            self.reference_pressure(),
+        );
+        if self.destructive {
+            defmt::write!(
+                f,
+                "BOOT_STATUS={=u8} IA={=u8} PH={=u8} PL={=u8} ",
+                self.flag(INT_SOURCE, Bitmasks::BOOT_STATUS),
+                self.flag(INT_SOURCE, Bitmasks::IA),
+                self.flag(INT_SOURCE, Bitmasks::PH),
+                self.flag(INT_SOURCE, Bitmasks::PL),
+            );
+        }
+        defmt::write!(
+            f,
+            "FSS={=u8} FTH_FIFO={=u8} OVR={=u8} P_DA={=u8} T_DA={=u8} P_OR={=u8} T_OR={=u8}",
+            self.value(FIFO_STATUS) & Bitmasks::FSS_MASK,
+            self.flag(FIFO_STATUS, Bitmasks::FTH_FIFO),
+            self.flag(FIFO_STATUS, Bitmasks::OVR),
+            self.flag(STATUS, Bitmasks::P_DA),
+            self.flag(STATUS, Bitmasks::T_DA),
+            self.flag(STATUS, Bitmasks::P_OR),
+            self.flag(STATUS, Bitmasks::T_OR),
+        );
+        if let (Some(pressure), Some(temperature)) = (self.pressure(), self.temperature()) {
+            defmt::write!(f, " P={=f32}hPa T={=f32}C", pressure, temperature);
+        }
+    }
+}
+
+impl<T, E, MODE> LPS22HB<T, MODE>
+where
+    T: Interface<Error = E>,
+{
+    /// Read the registers that can be read without side effects (all but INT_SOURCE,
+    /// the output registers and LPFP_RES), one burst read per block of contiguous registers
+    pub fn dump_config_registers(&mut self) -> Result<RegisterDump, T::Error> {
+        self.dump(false)
+    }
+
+    /// Read all the registers (except LPFP_RES), one burst read per block of contiguous registers.
+    /// __NOTE__: this clears the latched interrupts in INT_SOURCE and the data available flags,
+    /// and takes the oldest sample out of the FIFO when it is enabled.
+    pub fn dump_registers_destructive(&mut self) -> Result<RegisterDump, T::Error> {
+        self.dump(true)
+    }
+
+    fn dump(&mut self, destructive: bool) -> Result<RegisterDump, T::Error> {
+        let mut dump = RegisterDump {
+            bytes: [0; 0x2D - FIRST as usize],
+            destructive,
+        };
+        for &(first, len) in dump.blocks().iter() {
+            let start = (first.addr() - FIRST) as usize;
+            self.read_block(first, &mut dump.bytes[start..start + len])?;
+        }
+        Ok(dump)
+    }
+}
@@ -5,6 +5,10 @@
 //! - check if all FIFO-related functions are implemented
 
 use super::*;
+use sensor::Measurement;
+
+/// Depth of the FIFO buffer (number of pressure and temperature samples)
+pub const FIFO_DEPTH: usize = 32;
 
 /// FIFO settings
 #[derive(Debug)]
@@ -33,14 +37,14 @@ impl FIFOConfig {
         let mut data = 0u8;
         // THIS RESULT MUST THEN BE COMBINED WITH THE OTHER BIT SETTINGS
         if self.enable_watermark.status() {
-            data |= 1 << 5;
+            data |= Bitmasks::STOP_ON_FTH;
         }
         data
     }
     fn f_fifo_ctrl(&self) -> u8 {
         let mut data = 0u8;
         data |= self.fifo_mode.value();
-        data |= self.watermark_level;
+        data |= self.watermark_level & Bitmasks::WTM_MASK;
         data
     }
 }
@@ -54,31 +58,34 @@ pub struct FifoStatus {
     pub fifo_level: u8,
 }
 
-impl<T, E> LPS22HB<T>
+impl<T, E, MODE> LPS22HB<T, MODE>
 where
     T: Interface<Error = E>,
+    MODE: Configurable,
 {
     // The FIFO buffer is enabled when the FIFO_EN bit in CTRL_REG2 (11h) is set to '1'
     // and each mode is selected by the FIFO_MODE[2:0] bits in FIFO_CTRL (14h).
     
     /// Enable and configure FIFO
     pub fn configure_fifo(&mut self, flag: FIFO_ON, config: FIFOConfig) -> Result<(), T::Error> {
-        match flag {
-            FIFO_ON::Enabled => self.set_register_bit_flag(Registers::CTRL_REG2, Bitmasks::FIFO_EN),
-            FIFO_ON::Disabled => self.clear_register_bit_flag(Registers::CTRL_REG2, Bitmasks::FIFO_EN),
-        }?;
-
-        match config.enable_watermark {
-            FLAG::Enabled => self.set_register_bit_flag(Registers::CTRL_REG2, Bitmasks::STOP_ON_FTH),
-            FLAG::Disabled => self.clear_register_bit_flag(Registers::CTRL_REG2, Bitmasks::STOP_ON_FTH),
-        }?;
+        // FIFO_EN and STOP_ON_FTH are both in CTRL_REG2, update them with a single write
+        let mut ctrl_reg2 = self.read_register(Registers::CTRL_REG2)? & !(Bitmasks::FIFO_EN | Bitmasks::STOP_ON_FTH);
+        if flag.status() {
+            ctrl_reg2 |= Bitmasks::FIFO_EN;
+        }
+        ctrl_reg2 |= config.f_ctrl_reg2();
+        self.write_register(Registers::CTRL_REG2, ctrl_reg2)?;
 
-        self.interface
-            .write(Registers::FIFO_CTRL.addr(), config.f_fifo_ctrl())?;
+        self.write_register(Registers::FIFO_CTRL, config.f_fifo_ctrl())?;
 
         Ok(())
     }
-    
+}
+
+impl<T, E, MODE> LPS22HB<T, MODE>
+where
+    T: Interface<Error = E>,
+{
     /// Get flags and FIFO level from the FIFO_STATUS register
     pub fn get_fifo_status(&mut self) -> Result<FifoStatus, T::Error> {
         
@@ -87,31 +94,29 @@ where
         let fifo_level_value = self.read_fifo_level()?;
 
         let status = FifoStatus {
-            /// Is FIFO filling equal or higher than the threshold?
-            fifo_thresh_reached: match reg_value & Bitmasks::FTH_FIFO {
-                0 => false,
-                _ => true,
-            },
-            /// Is FIFO full and at least one sample has been overwritten?
-            fifo_overrun: match reg_value & Bitmasks::OVR {
-                0 => false,
-                _ => true,
-            },
-            
-            fifo_empty: match fifo_level_value {
-                0 => true,
-                _ => false,
-            },
-             
-            /// Read FIFO stored data level
-            
-            // replace with a bitmask?
-
+            // Is FIFO filling equal or higher than the threshold?
+            fifo_thresh_reached: reg_value & Bitmasks::FTH_FIFO != 0,
+            // Is FIFO full and at least one sample has been overwritten?
+            fifo_overrun: reg_value & Bitmasks::OVR != 0,
+            // Is FIFO empty?
+            fifo_empty: fifo_level_value == 0,
+            // FIFO stored data level
             fifo_level: fifo_level_value,
         };
         Ok(status)
     }
     
+    /// Read the samples currently stored in the FIFO into `buffer`, oldest first.
+    /// Reads at most `buffer.len()` samples and returns the number of samples read.
+    pub fn read_fifo(&mut self, buffer: &mut [Measurement]) -> Result<usize, T::Error> {
+        let level = self.read_fifo_level()? as usize;
+        let count = level.min(buffer.len());
+        for sample in buffer.iter_mut().take(count) {
+            *sample = self.read_measurement()?;
+        }
+        Ok(count)
+    }
+
     /// Read FIFO stored data level   
     fn read_fifo_level(&mut self) -> Result<u8, T::Error> {
         let mut data = [0u8; 1];
@@ -0,0 +1,343 @@
+//! Fixed-size digital filters for smoothing the measurement stream in firmware
+//!
+//! These complement the on-chip low-pass filter (see `lowpass_filter()`).
+//! All filters work on `f32` values as well as on the raw fixed-point values
+//! (`i32` pressure, `i16` temperature), and can be paired to filter a whole
+//! `Measurement` or `RawMeasurement`, see [`Paired`].
+
+use core::ops::{Add, Sub};
+use sensor::{Measurement, RawMeasurement};
+
+use super::*;
+
+/// A value that can be filtered
+pub trait Sample: Copy + PartialOrd + Default {
+    /// Wider type used to accumulate sums without overflow or loss of precision
+    type Acc: Copy + Default + Add<Output = Self::Acc> + Sub<Output = Self::Acc>;
+    /// Convert to the accumulator type
+    fn widen(self) -> Self::Acc;
+    /// Mean of `n` accumulated samples
+    fn mean(sum: Self::Acc, n: usize) -> Self;
+    /// Convert to `f32`
+    fn to_f32(self) -> f32;
+    /// Convert from `f32`, rounding to the nearest value
+    fn from_f32(value: f32) -> Self;
+}
+
+impl Sample for f32 {
+    type Acc = f32;
+    fn widen(self) -> f32 {
+        self
+    }
+    fn mean(sum: f32, n: usize) -> Self {
+        sum / n as f32
+    }
+    fn to_f32(self) -> f32 {
+        self
+    }
+    fn from_f32(value: f32) -> Self {
+        value
+    }
+}
+
+impl Sample for i32 {
+    type Acc = i64;
+    fn widen(self) -> i64 {
+        self as i64
+    }
+    fn mean(sum: i64, n: usize) -> Self {
+        let n = n as i64;
+        // round half away from zero
+        let half = if sum < 0 { -n / 2 } else { n / 2 };
+        ((sum + half) / n) as i32
+    }
+    fn to_f32(self) -> f32 {
+        self as f32
+    }
+    fn from_f32(value: f32) -> Self {
+        libm::roundf(value) as i32
+    }
+}
+
+impl Sample for i16 {
+    type Acc = i32;
+    fn widen(self) -> i32 {
+        self as i32
+    }
+    fn mean(sum: i32, n: usize) -> Self {
+        <i32 as Sample>::mean(sum as i64, n) as i16
+    }
+    fn to_f32(self) -> f32 {
+        self as f32
+    }
+    fn from_f32(value: f32) -> Self {
+        libm::roundf(value) as i16
+    }
+}
+
+/// Common filter interface
+pub trait Filter<S> {
+    /// Feed a new sample and return the filtered value
+    fn update(&mut self, sample: S) -> S;
+    /// Forget all previous samples
+    fn reset(&mut self);
+}
+
+/// Moving average over the last `N` samples (`N` must not be 0)
+#[derive(Debug, Clone)]
+pub struct MovingAverage<S: Sample, const N: usize> {
+    buffer: [S; N],
+    index: usize,
+    count: usize,
+    sum: S::Acc,
+}
+
+impl<S: Sample, const N: usize> MovingAverage<S, N> {
+    const NON_EMPTY: () = assert!(N > 0, "MovingAverage needs at least one sample");
+
+    /// Create an empty moving average filter
+    pub fn new() -> Self {
+        let () = Self::NON_EMPTY;
+        MovingAverage {
+            buffer: [S::default(); N],
+            index: 0,
+            count: 0,
+            sum: S::Acc::default(),
+        }
+    }
+}
+
+impl<S: Sample, const N: usize> Default for MovingAverage<S, N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<S: Sample, const N: usize> Filter<S> for MovingAverage<S, N> {
+    fn update(&mut self, sample: S) -> S {
+        if self.count == N {
+            self.sum = self.sum - self.buffer[self.index].widen();
+        } else {
+            self.count += 1;
+        }
+        self.buffer[self.index] = sample;
+        self.sum = self.sum + sample.widen();
+        self.index = (self.index + 1) % N;
+        if self.index == 0 {
+            // recompute the sum once per cycle, so that float rounding errors don't accumulate
+            self.sum = self
+                .buffer
+                .iter()
+                .fold(S::Acc::default(), |sum, x| sum + x.widen());
+        }
+        S::mean(self.sum, self.count)
+    }
+
+    fn reset(&mut self) {
+        *self = Self::new();
+    }
+}
+
+/// Exponential (first-order IIR) filter: `y = y + alpha * (x - y)`
+#[derive(Debug, Clone)]
+pub struct ExponentialFilter {
+    alpha: f32,
+    state: Option<f32>,
+}
+
+impl ExponentialFilter {
+    /// Create a filter with the smoothing factor `alpha` (0 < alpha <= 1, smaller is smoother)
+    pub fn new(alpha: f32) -> Self {
+        ExponentialFilter {
+            alpha: alpha.clamp(f32::EPSILON, 1.0),
+            state: None,
+        }
+    }
+
+    /// Create a filter with the given time constant, for samples taken every `period` seconds
+    pub fn with_time_constant(time_constant: f32, period: f32) -> Self {
+        Self::new(period / (time_constant + period))
+    }
+
+    /// Forget all previous samples
+    pub fn reset(&mut self) {
+        self.state = None;
+    }
+}
+
+impl<S: Sample> Filter<S> for ExponentialFilter {
+    fn update(&mut self, sample: S) -> S {
+        let x = sample.to_f32();
+        let y = match self.state {
+            Some(y) => y + self.alpha * (x - y),
+            None => x,
+        };
+        self.state = Some(y);
+        S::from_f32(y)
+    }
+
+    fn reset(&mut self) {
+        ExponentialFilter::reset(self);
+    }
+}
+
+/// Median of the last `N` samples, removes spikes without smearing steps (`N` must not be 0)
+#[derive(Debug, Clone)]
+pub struct MedianFilter<S: Sample, const N: usize> {
+    buffer: [S; N],
+    index: usize,
+    count: usize,
+}
+
+impl<S: Sample, const N: usize> MedianFilter<S, N> {
+    const NON_EMPTY: () = assert!(N > 0, "MedianFilter needs at least one sample");
+
+    /// Create an empty median filter
+    pub fn new() -> Self {
+        let () = Self::NON_EMPTY;
+        MedianFilter {
+            buffer: [S::default(); N],
+            index: 0,
+            count: 0,
+        }
+    }
+}
+
+impl<S: Sample, const N: usize> Default for MedianFilter<S, N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<S: Sample, const N: usize> Filter<S> for MedianFilter<S, N> {
+    fn update(&mut self, sample: S) -> S {
+        self.buffer[self.index] = sample;
+        self.index = (self.index + 1) % N;
+        self.count = (self.count + 1).min(N);
+
+        // insertion sort of a copy, N is small
+        let mut sorted = self.buffer;
+        let sorted = &mut sorted[..self.count];
+        for i in 1..sorted.len() {
+            let mut j = i;
+            while j > 0 && sorted[j - 1] > sorted[j] {
+                sorted.swap(j - 1, j);
+                j -= 1;
+            }
+        }
+        let mid = sorted.len() / 2;
+        if sorted.len() % 2 == 1 {
+            sorted[mid]
+        } else {
+            S::mean(sorted[mid - 1].widen() + sorted[mid].widen(), 2)
+        }
+    }
+
+    fn reset(&mut self) {
+        *self = Self::new();
+    }
+}
+
+/// One-dimensional Kalman filter for a (slowly varying) constant value
+#[derive(Debug, Clone)]
+pub struct Kalman1D {
+    process_noise: f32,
+    measurement_noise: f32,
+    estimate: Option<f32>,
+    error: f32,
+}
+
+impl Kalman1D {
+    /// Create a filter with the given process noise variance (how fast the true value may change per sample)
+    /// and measurement noise variance (square of the sensor RMS noise, in the same units as the samples)
+    pub fn new(process_noise: f32, measurement_noise: f32) -> Self {
+        Kalman1D {
+            process_noise,
+            measurement_noise,
+            estimate: None,
+            error: measurement_noise,
+        }
+    }
+
+    /// Current estimate variance
+    pub fn error_variance(&self) -> f32 {
+        self.error
+    }
+
+    /// Forget all previous samples
+    pub fn reset(&mut self) {
+        self.estimate = None;
+        self.error = self.measurement_noise;
+    }
+}
+
+impl<S: Sample> Filter<S> for Kalman1D {
+    fn update(&mut self, sample: S) -> S {
+        let z = sample.to_f32();
+        let x = match self.estimate {
+            None => {
+                self.error = self.measurement_noise;
+                z
+            }
+            Some(x) => {
+                let p = self.error + self.process_noise;
+                let gain = p / (p + self.measurement_noise);
+                self.error = (1.0 - gain) * p;
+                x + gain * (z - x)
+            }
+        };
+        self.estimate = Some(x);
+        S::from_f32(x)
+    }
+
+    fn reset(&mut self) {
+        Kalman1D::reset(self);
+    }
+}
+
+/// Two filters applied to the pressure and temperature channels of a measurement
+#[derive(Debug, Clone, Default)]
+pub struct Paired<P, T> {
+    /// Filter for the pressure channel
+    pub pressure: P,
+    /// Filter for the temperature channel
+    pub temperature: T,
+}
+
+impl<P, T> Paired<P, T> {
+    /// Combine a pressure and a temperature filter
+    pub fn new(pressure: P, temperature: T) -> Self {
+        Paired {
+            pressure,
+            temperature,
+        }
+    }
+}
+
+impl<P: Filter<f32>, T: Filter<f32>> Filter<Measurement> for Paired<P, T> {
+    fn update(&mut self, sample: Measurement) -> Measurement {
+        Measurement {
+            pressure: self.pressure.update(sample.pressure),
+            temperature: self.temperature.update(sample.temperature),
+        }
+    }
+
+    fn reset(&mut self) {
+        self.pressure.reset();
+        self.temperature.reset();
+    }
+}
+
+impl<P: Filter<i32>, T: Filter<i16>> Filter<RawMeasurement> for Paired<P, T> {
+    fn update(&mut self, sample: RawMeasurement) -> RawMeasurement {
+        RawMeasurement {
+            pressure: self.pressure.update(sample.pressure),
+            temperature: self.temperature.update(sample.temperature),
+        }
+    }
+
+    fn reset(&mut self) {
+        self.pressure.reset();
+        self.temperature.reset();
+    }
+}
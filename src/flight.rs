@@ -0,0 +1,270 @@
+//! Model rocket flight event detection: launch, burnout, apogee and landing
+//!
+//! Feed the detector with pressure readings at a fixed data rate, typically
+//! 75 Hz bursts read from the FIFO. Times are in seconds since the first sample.
+//!
+//! Around Mach 1 shock waves disturb the static pressure and can fake a sudden
+//! altitude change. Readings too far from the predicted altitude are rejected
+//! (a step that persists moves the altitude without disturbing the speed),
+//! and burnout and apogee are not evaluated above a lockout speed.
+
+use super::*;
+use sensor::Measurement;
+use variometer::{Variometer, VariometerConfig};
+
+/// Flight detector settings
+#[derive(Debug, Clone, Copy)]
+pub struct FlightConfig {
+    /// Height above the pad (m) that confirms a launch
+    pub launch_altitude: f32,
+    /// Vertical speed (m/s) below which the rocket is considered at rest
+    pub rest_speed: f32,
+    /// Drop (m/s) below the peak vertical speed that confirms burnout
+    pub burnout_speed_drop: f32,
+    /// Drop (m) below the peak altitude that confirms apogee
+    pub apogee_altitude_drop: f32,
+    /// Height above the pad (m) below which a landing can be detected
+    pub landing_altitude: f32,
+    /// Time (s) at rest needed to confirm the landing
+    pub landing_time: f32,
+    /// Largest accepted distance (m) between a reading and the predicted altitude
+    pub max_residual: f32,
+    /// Number of consecutive rejected readings after which the altitude moves to the readings,
+    /// keeping the vertical speed
+    pub max_rejected: u8,
+    /// Burnout and apogee are not evaluated above this vertical speed (m/s)
+    pub mach_lockout_speed: f32,
+    /// Altitude gain of the vertical speed filter, see `VariometerConfig::critically_damped`
+    pub smoothing: f32,
+}
+
+impl Default for FlightConfig {
+    fn default() -> Self {
+        FlightConfig {
+            launch_altitude: 10.0,
+            rest_speed: 2.0,
+            burnout_speed_drop: 3.0,
+            apogee_altitude_drop: 5.0,
+            landing_altitude: 30.0,
+            landing_time: 3.0,
+            max_residual: 20.0,
+            max_rejected: 15,
+            mach_lockout_speed: 250.0,
+            smoothing: 0.2,
+        }
+    }
+}
+
+/// Flight phase
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FlightPhase {
+    /// Waiting on the pad
+    Pad,
+    /// Motor burning
+    Boost,
+    /// Coasting up after burnout
+    Coast,
+    /// Descending after apogee
+    Descent,
+    /// Landed
+    Landed,
+}
+
+/// Kind of flight event
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FlightEventKind {
+    /// Lift-off
+    Launch,
+    /// End of the motor burn (peak vertical speed)
+    Burnout,
+    /// Highest point
+    Apogee,
+    /// Back on the ground
+    Landing,
+}
+
+/// Flight event
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FlightEvent {
+    /// What happened
+    pub kind: FlightEventKind,
+    /// When it happened, in seconds since the first sample
+    pub time: f32,
+    /// Altitude above the pad at that time, in metres
+    pub altitude: f32,
+}
+
+/// Flight event detector
+#[derive(Debug, Clone)]
+pub struct FlightDetector {
+    config: FlightConfig,
+    vario: Variometer,
+    phase: FlightPhase,
+    samples: u32,
+    ground: Option<f32>,
+    rejected: u8,
+    last_rest: f32,
+    last_motion: f32,
+    peak_speed: (f32, f32),
+    peak_altitude: (f32, f32),
+}
+
+impl FlightDetector {
+    /// Create a detector for samples produced at the given data rate.
+    /// Returns `None` for `ODR::PowerDown`.
+    pub fn new(odr: ODR, config: FlightConfig) -> Option<Self> {
+        odr.period().map(|period| Self::with_period(period, config))
+    }
+
+    /// Create a detector for samples taken every `period` seconds
+    pub fn with_period(period: f32, config: FlightConfig) -> Self {
+        FlightDetector {
+            vario: Variometer::with_period(
+                period,
+                VariometerConfig::critically_damped(config.smoothing),
+            ),
+            config,
+            phase: FlightPhase::Pad,
+            samples: 0,
+            ground: None,
+            rejected: 0,
+            last_rest: 0.0,
+            last_motion: 0.0,
+            peak_speed: (0.0, 0.0),
+            peak_altitude: (0.0, 0.0),
+        }
+    }
+
+    /// Current flight phase
+    pub fn phase(&self) -> FlightPhase {
+        self.phase
+    }
+
+    /// Time of the latest sample, in seconds since the first sample
+    pub fn time(&self) -> f32 {
+        self.samples.saturating_sub(1) as f32 * self.vario.period()
+    }
+
+    /// Filtered altitude above the pad, in metres
+    pub fn altitude(&self) -> f32 {
+        let altitude = self.vario.output().map_or(0.0, |o| o.altitude);
+        altitude - self.ground.unwrap_or(altitude)
+    }
+
+    /// Filtered vertical speed, in m/s
+    pub fn vertical_speed(&self) -> f32 {
+        self.vario.output().map_or(0.0, |o| o.vertical_speed)
+    }
+
+    /// Feed a burst of samples (e.g. read with `read_fifo()`), oldest first,
+    /// calling `on_event` for every detected event
+    pub fn update_burst<F: FnMut(FlightEvent)>(
+        &mut self,
+        samples: &[Measurement],
+        mut on_event: F,
+    ) {
+        for sample in samples {
+            if let Some(event) = self.update(sample.pressure) {
+                on_event(event);
+            }
+        }
+    }
+
+    /// Feed a pressure reading (hPa)
+    pub fn update(&mut self, pressure: f32) -> Option<FlightEvent> {
+        self.samples += 1;
+        self.filter(pressure);
+
+        let time = self.time();
+        let absolute = self.vario.output().map_or(0.0, |o| o.altitude);
+        let ground = *self.ground.get_or_insert(absolute);
+        let altitude = absolute - ground;
+        let speed = self.vertical_speed();
+        let config = self.config;
+        let event = |kind, (time, altitude)| {
+            Some(FlightEvent {
+                kind,
+                time,
+                altitude,
+            })
+        };
+
+        if libm::fabsf(speed) > config.rest_speed {
+            self.last_motion = time;
+        } else {
+            self.last_rest = time;
+        }
+
+        match self.phase {
+            FlightPhase::Pad => {
+                if altitude > config.launch_altitude {
+                    self.phase = FlightPhase::Boost;
+                    self.peak_speed = (time, speed);
+                    self.peak_altitude = (time, altitude);
+                    return event(FlightEventKind::Launch, (self.last_rest, 0.0));
+                }
+                // follow slow pressure changes while waiting on the pad
+                if time - self.last_motion > 1.0 {
+                    self.ground = Some(ground + 0.01 * altitude);
+                }
+                None
+            }
+            FlightPhase::Boost => {
+                if speed > self.peak_speed.1 {
+                    self.peak_speed = (time, speed);
+                    self.peak_altitude = (time, altitude);
+                }
+                if speed < config.mach_lockout_speed
+                    && speed < self.peak_speed.1 - config.burnout_speed_drop
+                {
+                    self.phase = FlightPhase::Coast;
+                    return event(FlightEventKind::Burnout, self.peak_altitude);
+                }
+                None
+            }
+            FlightPhase::Coast => {
+                if altitude > self.peak_altitude.1 {
+                    self.peak_altitude = (time, altitude);
+                }
+                if speed < config.mach_lockout_speed
+                    && altitude < self.peak_altitude.1 - config.apogee_altitude_drop
+                {
+                    self.phase = FlightPhase::Descent;
+                    return event(FlightEventKind::Apogee, self.peak_altitude);
+                }
+                None
+            }
+            FlightPhase::Descent => {
+                if altitude < config.landing_altitude
+                    && time - self.last_motion >= config.landing_time
+                {
+                    self.phase = FlightPhase::Landed;
+                    return event(FlightEventKind::Landing, (self.last_motion, altitude));
+                }
+                None
+            }
+            FlightPhase::Landed => None,
+        }
+    }
+
+    /// Update the altitude filter, rejecting readings too far from the prediction
+    fn filter(&mut self, pressure: f32) {
+        if let Some(previous) = self.vario.output() {
+            let predicted = previous.altitude + previous.vertical_speed * self.vario.period();
+            let measured = altitude::altitude_from_pressure(pressure, altitude::STANDARD_PRESSURE);
+            if libm::fabsf(measured - predicted) > self.config.max_residual {
+                if self.rejected < self.config.max_rejected {
+                    self.rejected += 1;
+                    self.vario.predict();
+                } else {
+                    // the step persists: take the new altitude, but keep it out of the speed
+                    self.rejected = 0;
+                    self.vario.reset_altitude(pressure);
+                }
+                return;
+            }
+        }
+        self.rejected = 0;
+        self.vario.update(pressure);
+    }
+}
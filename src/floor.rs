@@ -0,0 +1,176 @@
+//! Indoor floor-change detection
+//!
+//! Tracks the altitude relative to a reference pressure captured at start-up,
+//! either in software (first reading) or with the AUTOZERO function,
+//! and reports a floor change once the device has settled on a new floor.
+//! The vertical speed during the move tells elevator rides apart from stairs.
+//! While the device is stationary the altitude baseline slowly follows the
+//! pressure, so that weather-driven drift over hours is not taken as a floor change.
+
+use super::*;
+use variometer::{Variometer, VariometerConfig};
+
+/// Floor detector settings
+#[derive(Debug, Clone, Copy)]
+pub struct FloorDetectorConfig {
+    /// Height of one floor, in metres
+    pub floor_height: f32,
+    /// Extra distance (metres) beyond half a floor needed to change floor
+    pub hysteresis: f32,
+    /// Vertical speed (m/s) above which a move is classified as an elevator ride
+    pub elevator_speed: f32,
+    /// Vertical speed (m/s) below which the device is considered stationary
+    pub stationary_speed: f32,
+    /// Time (s) the device must be stationary before the floor is evaluated
+    pub settle_time: f32,
+    /// Time constant (s) of the baseline drift compensation while stationary
+    pub drift_time_constant: f32,
+    /// Altitude gain of the vertical speed filter, see `VariometerConfig::critically_damped`
+    pub smoothing: f32,
+}
+
+impl Default for FloorDetectorConfig {
+    fn default() -> Self {
+        FloorDetectorConfig {
+            floor_height: 3.0,
+            hysteresis: 0.5,
+            elevator_speed: 0.6,   // stairs are climbed at about 0.2-0.4 m/s, elevators move at 1 m/s or more
+            stationary_speed: 0.1,
+            settle_time: 3.0,
+            drift_time_constant: 300.0,
+            smoothing: 0.05,
+        }
+    }
+}
+
+/// How the floor change happened
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Transport {
+    /// Slow vertical speed
+    Stairs,
+    /// Fast vertical speed
+    Elevator,
+}
+
+/// Floor change event
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FloorEvent {
+    /// Previous floor, relative to the reference floor
+    pub from: i32,
+    /// New floor, relative to the reference floor
+    pub to: i32,
+    /// Classification of the move
+    pub transport: Transport,
+}
+
+/// Floor-change detector
+#[derive(Debug, Clone)]
+pub struct FloorDetector {
+    config: FloorDetectorConfig,
+    vario: Variometer,
+    reference: Option<f32>,
+    altitude: f32,
+    drift: f32,
+    floor: i32,
+    still_time: f32,
+    peak_speed: f32,
+}
+
+impl FloorDetector {
+    /// Create a detector for samples produced at the given data rate.
+    /// Returns `None` for `ODR::PowerDown`.
+    pub fn new(odr: ODR, config: FloorDetectorConfig) -> Option<Self> {
+        odr.period().map(|period| Self::with_period(period, config))
+    }
+
+    /// Create a detector for samples taken every `period` seconds
+    pub fn with_period(period: f32, config: FloorDetectorConfig) -> Self {
+        FloorDetector {
+            vario: Variometer::with_period(
+                period,
+                VariometerConfig::critically_damped(config.smoothing),
+            ),
+            config,
+            reference: None,
+            altitude: 0.0,
+            drift: 0.0,
+            floor: 0,
+            still_time: 0.0,
+            peak_speed: 0.0,
+        }
+    }
+
+    /// Set the reference pressure (hPa) of floor 0 and restart tracking.
+    /// When AUTOZERO is used, pass the value of `read_reference_pressure()`.
+    pub fn set_reference(&mut self, pressure: f32) {
+        self.reference = Some(pressure);
+        self.vario.set_reference_pressure(pressure);
+        self.vario.reset();
+        self.altitude = 0.0;
+        self.drift = 0.0;
+        self.floor = 0;
+        self.still_time = 0.0;
+        self.peak_speed = 0.0;
+    }
+
+    /// Feed an absolute pressure reading (hPa). The first reading becomes the reference
+    /// unless one was set with [`FloorDetector::set_reference`].
+    pub fn update(&mut self, pressure: f32) -> Option<FloorEvent> {
+        if self.reference.is_none() {
+            self.set_reference(pressure);
+        }
+        let output = self.vario.update(pressure);
+        let period = self.vario.period();
+        let speed = libm::fabsf(output.vertical_speed);
+        self.altitude = output.altitude - self.drift;
+
+        if speed > self.config.stationary_speed {
+            self.still_time = 0.0;
+            self.peak_speed = self.peak_speed.max(speed);
+            return None;
+        }
+        self.still_time += period;
+        if self.still_time < self.config.settle_time {
+            return None;
+        }
+
+        let height = self.config.floor_height;
+        let offset = self.altitude - self.floor as f32 * height;
+        if libm::fabsf(offset) > height / 2.0 + self.config.hysteresis {
+            let event = FloorEvent {
+                from: self.floor,
+                to: libm::roundf(self.altitude / height) as i32,
+                transport: if self.peak_speed > self.config.elevator_speed {
+                    Transport::Elevator
+                } else {
+                    Transport::Stairs
+                },
+            };
+            self.floor = event.to;
+            self.peak_speed = 0.0;
+            return Some(event);
+        }
+
+        // settled on the current floor: let the baseline follow slow pressure changes
+        self.drift += offset * period / self.config.drift_time_constant;
+        self.peak_speed = 0.0;
+        None
+    }
+
+    /// Feed a pressure difference from the reference (hPa), as output with AUTOZERO enabled.
+    /// The reference must have been set with [`FloorDetector::set_reference`].
+    pub fn update_differential(&mut self, difference: f32) -> Option<FloorEvent> {
+        let reference = self.reference?;
+        self.update(reference + difference)
+    }
+
+    /// Current floor, relative to the reference floor
+    pub fn floor(&self) -> i32 {
+        self.floor
+    }
+
+    /// Altitude relative to the reference floor (metres), with drift compensation
+    pub fn relative_altitude(&self) -> f32 {
+        self.altitude
+    }
+}
@@ -1,7 +1,15 @@
 //! I2C Interface
+use super::shared::{BorrowedI2c, NoBus};
 use super::Interface;
+use crate::shadow::Shadow;
+use crate::LPS22HB;
+use core::marker::PhantomData;
+use core::mem;
 use embedded_hal::blocking::i2c::{Write, WriteRead};
 
+/// Maximum number of data bytes sent in one I2C write transaction
+const MAX_BURST: usize = 16;
+
 /// Errors in this crate
 #[derive(Debug)]
 pub enum Error<CommE> {
@@ -43,6 +51,84 @@ impl<I2C> I2cInterface<I2C> {
     }
 }
 
+impl<'a, I2C> I2cInterface<BorrowedI2c<'a, I2C>> {
+    /// Initializes an Interface borrowing the `I2C` instance, which is released
+    /// when the interface (or the driver instance holding it) is dropped.
+    ///
+    /// A driver instance created on it starts over from an empty shadow copy, with
+    /// IF_ADD_INC assumed enabled, and in the `Untyped` mode. To keep one driver
+    /// instance and hand it the bus for each access, use `LPS22HB::detached`.
+    /// # Arguments
+    /// * `i2c` - I2C instance
+    /// * `dev_addr` - `I2cAddress`: register address for the pressure sensor
+    pub fn borrowed(i2c: &'a mut I2C, dev_addr: I2cAddress) -> Self {
+        Self::init(BorrowedI2c::new(i2c), dev_addr)
+    }
+}
+
+impl LPS22HB<I2cInterface<NoBus>> {
+    /// Create a driver instance without a bus, which is handed the `I2C` instance
+    /// for each access with `with_bus` or `attach`. The shadow copy, the IF_ADD_INC
+    /// state and the typestate mode are kept between the accesses.
+    /// # Arguments
+    /// * `dev_addr` - `I2cAddress`: register address for the pressure sensor
+    pub fn detached(dev_addr: I2cAddress) -> Self {
+        LPS22HB {
+            interface: I2cInterface::init(NoBus, dev_addr),
+            shadow: Shadow::new(),
+            mode: PhantomData,
+        }
+    }
+}
+
+impl<MODE> LPS22HB<I2cInterface<NoBus>, MODE> {
+    /// Lend `i2c` to the driver for the accesses made in `f`, e.g.
+    /// `lps22.with_bus(&mut i2c, |lps22| lps22.read_pressure())`
+    pub fn with_bus<'a, I2C, R, F>(&mut self, i2c: &'a mut I2C, f: F) -> R
+    where
+        F: FnOnce(&mut LPS22HB<I2cInterface<BorrowedI2c<'a, I2C>>, MODE>) -> R,
+    {
+        let mut lps22 = LPS22HB {
+            interface: I2cInterface {
+                i2c: BorrowedI2c::new(i2c),
+                dev_addr: self.interface.dev_addr,
+            },
+            shadow: mem::replace(&mut self.shadow, Shadow::new()),
+            mode: PhantomData,
+        };
+        let result = f(&mut lps22);
+        self.shadow = lps22.shadow;
+        result
+    }
+
+    /// Lend `i2c` to the driver until `detach`, e.g. for a mode transition:
+    /// `lps22.attach(&mut i2c).into_power_down()?.detach()`
+    pub fn attach<I2C>(self, i2c: &mut I2C) -> LPS22HB<I2cInterface<BorrowedI2c<'_, I2C>>, MODE> {
+        LPS22HB {
+            interface: I2cInterface {
+                i2c: BorrowedI2c::new(i2c),
+                dev_addr: self.interface.dev_addr,
+            },
+            shadow: self.shadow,
+            mode: PhantomData,
+        }
+    }
+}
+
+impl<'a, I2C, MODE> LPS22HB<I2cInterface<BorrowedI2c<'a, I2C>>, MODE> {
+    /// Release the borrowed bus, keeping the driver state
+    pub fn detach(self) -> LPS22HB<I2cInterface<NoBus>, MODE> {
+        LPS22HB {
+            interface: I2cInterface {
+                i2c: NoBus,
+                dev_addr: self.interface.dev_addr,
+            },
+            shadow: self.shadow,
+            mode: PhantomData,
+        }
+    }
+}
+
 /// Implementation of `Interface`
 impl<I2C, CommE> Interface for I2cInterface<I2C>
 where
@@ -52,21 +138,30 @@ where
 
     fn write(&mut self, addr: u8, value: u8) -> Result<(), Self::Error> {
         //let sensor_addr = self.dev_addr;        
-        core::prelude::v1::Ok(
-            self.i2c
-                //.write(sensor_addr, &[addr, value])
-                .write(self.dev_addr, &[addr, value])
-                .map_err(Error::Comm)?,
-        )
+        self.i2c
+            //.write(sensor_addr, &[addr, value])
+            .write(self.dev_addr, &[addr, value])
+            .map_err(Error::Comm)
     }
 
     fn read(&mut self, addr: u8, buffer: &mut [u8]) -> Result<(), Self::Error> {
         //let sensor_addr = self.dev_addr;        
-        core::prelude::v1::Ok(
+        self.i2c
+            //.write_read(sensor_addr, &[addr], buffer)
+            .write_read(self.dev_addr, &[addr], buffer)
+            .map_err(Error::Comm)
+    }
+
+    fn write_multiple(&mut self, addr: u8, values: &[u8]) -> Result<(), Self::Error> {
+        // register address followed by the data, longer writes are split
+        let mut payload = [0u8; MAX_BURST + 1];
+        for (i, chunk) in values.chunks(MAX_BURST).enumerate() {
+            payload[0] = addr.wrapping_add((i * MAX_BURST) as u8);
+            payload[1..=chunk.len()].copy_from_slice(chunk);
             self.i2c
-                //.write_read(sensor_addr, &[addr], buffer)
-                .write_read(self.dev_addr, &[addr], buffer)
-                .map_err(Error::Comm)?,
-        )
+                .write(self.dev_addr, &payload[..=chunk.len()])
+                .map_err(Error::Comm)?;
+        }
+        Ok(())
     }
 }
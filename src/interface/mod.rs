@@ -1,8 +1,14 @@
 //! Interface trait
-//pub mod spi;
-//pub use self::spi::SpiInterface;
+pub mod spi;
+pub use self::spi::SpiInterface;
 pub mod i2c;
 pub use self::i2c::I2cInterface;
+pub mod shared;
+pub use self::shared::{BorrowedI2c, NoBus, SharedI2c};
+pub mod tracing;
+pub use self::tracing::TracingInterface;
+pub mod replay;
+pub use self::replay::{RecordingInterface, ReplayInterface};
 
 /// Interface Trait. `SpiInterface` and `I2cInterface` implement this.
 pub trait Interface {
@@ -17,4 +23,19 @@ pub trait Interface {
     /// * `addr` - register address
     /// * `buffer` - buffer to store read data
     fn read(&mut self, addr: u8, buffer: &mut [u8]) -> Result<(), Self::Error>;
+    /// Writes multiple bytes to consecutive registers starting at the specified address.
+    /// Implementations should use a single bus transaction, so that the sensor never
+    /// sees a partially updated multi-byte value; this relies on the register address
+    /// auto-increment (IF_ADD_INC, enabled by default), the driver writes one register
+    /// at a time while it is cleared.
+    /// The default implementation writes the bytes one by one.
+    /// # Arguments    
+    /// * `addr` - address of the first register
+    /// * `values` - values to write
+    fn write_multiple(&mut self, addr: u8, values: &[u8]) -> Result<(), Self::Error> {
+        for (i, value) in values.iter().enumerate() {
+            self.write(addr.wrapping_add(i as u8), *value)?;
+        }
+        Ok(())
+    }
 }
\ No newline at end of file
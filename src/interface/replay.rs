@@ -0,0 +1,241 @@
+//! Record-and-replay interfaces for hardware-free regression tests
+//!
+//! [`RecordingInterface`] wraps the interface of a real board and serialises
+//! each successful transaction to a byte buffer:
+//! - write: `b'W'`, register address, value
+//! - multiple byte write: `b'M'`, address of the first register, number of bytes (at most 255), bytes written
+//! - read: `b'R'`, register address, number of bytes (at most 255), bytes read
+//!
+//! [`ReplayInterface`] serves the recorded reads back and checks that the
+//! driver performs exactly the recorded transactions, so any sequence of
+//! driver calls becomes a deterministic test.
+//!
+//! ```rust,ignore
+//! // on the board
+//! let mut lps22 = LPS22HB::new(RecordingInterface::<_, 512>::new(i2c_interface));
+//! lps22.read_fifo(&mut samples)?;
+//! let recording = lps22.destroy();
+//! dump(recording.as_bytes());
+//!
+//! // in a host test
+//! let mut lps22 = LPS22HB::new(ReplayInterface::new(&RECORDED_BYTES));
+//! assert_eq!(lps22.read_fifo(&mut samples).unwrap(), 32);
+//! assert!(lps22.destroy().is_finished());
+//! ```
+
+use super::tracing::Transaction;
+use super::Interface;
+
+/// Tag of a recorded write
+const TAG_WRITE: u8 = b'W';
+/// Tag of a recorded multiple byte write
+const TAG_WRITE_MULTIPLE: u8 = b'M';
+/// Tag of a recorded read
+const TAG_READ: u8 = b'R';
+
+/// Interface wrapper serialising the transactions into a buffer of `N` bytes
+#[derive(Debug)]
+pub struct RecordingInterface<I, const N: usize> {
+    interface: I,
+    buffer: [u8; N],
+    len: usize,
+    truncated: bool,
+}
+
+impl<I: Interface, const N: usize> RecordingInterface<I, N> {
+    /// Start recording the transactions of `interface`
+    pub fn new(interface: I) -> Self {
+        RecordingInterface {
+            interface,
+            buffer: [0; N],
+            len: 0,
+            truncated: false,
+        }
+    }
+
+    /// Return the wrapped interface
+    pub fn destroy(self) -> I {
+        self.interface
+    }
+
+    /// Access the wrapped interface without recording
+    pub fn interface_mut(&mut self) -> &mut I {
+        &mut self.interface
+    }
+
+    /// The recording
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.buffer[..self.len]
+    }
+
+    /// Has a transaction been left out because the buffer was full?
+    /// The recording stops at the first transaction that does not fit.
+    pub fn is_truncated(&self) -> bool {
+        self.truncated
+    }
+
+    /// Discard the recording
+    pub fn clear(&mut self) {
+        self.len = 0;
+        self.truncated = false;
+    }
+
+    fn append(&mut self, header: &[u8], data: &[u8]) {
+        let end = self.len + header.len() + data.len();
+        if self.truncated || end > N {
+            self.truncated = true;
+            return;
+        }
+        self.buffer[self.len..self.len + header.len()].copy_from_slice(header);
+        self.buffer[self.len + header.len()..end].copy_from_slice(data);
+        self.len = end;
+    }
+
+    fn append_block(&mut self, tag: u8, addr: u8, data: &[u8]) {
+        if data.len() > u8::MAX as usize {
+            self.truncated = true;
+        } else {
+            self.append(&[tag, addr, data.len() as u8], data);
+        }
+    }
+}
+
+impl<I: Interface, const N: usize> Interface for RecordingInterface<I, N> {
+    type Error = I::Error;
+
+    fn write(&mut self, addr: u8, value: u8) -> Result<(), Self::Error> {
+        self.interface.write(addr, value)?;
+        self.append(&[TAG_WRITE, addr, value], &[]);
+        Ok(())
+    }
+
+    fn read(&mut self, addr: u8, buffer: &mut [u8]) -> Result<(), Self::Error> {
+        self.interface.read(addr, buffer)?;
+        self.append_block(TAG_READ, addr, buffer);
+        Ok(())
+    }
+
+    fn write_multiple(&mut self, addr: u8, values: &[u8]) -> Result<(), Self::Error> {
+        self.interface.write_multiple(addr, values)?;
+        self.append_block(TAG_WRITE_MULTIPLE, addr, values);
+        Ok(())
+    }
+}
+
+/// Errors reported by the replay
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ReplayError {
+    /// The driver performed more transactions than recorded
+    EndOfRecording(Transaction),
+    /// The driver performed `actual` where `expected` was recorded, at byte `offset` of the recording
+    Mismatch {
+        /// Position of the recorded transaction
+        offset: usize,
+        /// Recorded transaction
+        expected: Transaction,
+        /// Transaction performed by the driver
+        actual: Transaction,
+    },
+    /// The driver wrote other bytes than recorded in the multiple byte write at byte `offset`
+    DataMismatch(usize),
+    /// Invalid or incomplete record at byte `offset` of the recording
+    Corrupt(usize),
+}
+
+/// Interface replaying a recording made with [`RecordingInterface`]
+#[derive(Debug, Clone)]
+pub struct ReplayInterface<'a> {
+    recording: &'a [u8],
+    position: usize,
+}
+
+impl<'a> ReplayInterface<'a> {
+    /// Replay `recording` from the start
+    pub fn new(recording: &'a [u8]) -> Self {
+        ReplayInterface {
+            recording,
+            position: 0,
+        }
+    }
+
+    /// Have all the recorded transactions been replayed?
+    pub fn is_finished(&self) -> bool {
+        self.position >= self.recording.len()
+    }
+
+    /// Position of the next transaction in the recording (bytes)
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    /// Check the next record against `actual`, return the recorded data
+    fn next(&mut self, actual: Transaction) -> Result<&'a [u8], ReplayError> {
+        let offset = self.position;
+        let record = &self.recording[offset..];
+        let (expected, data) = match *record {
+            [] => return Err(ReplayError::EndOfRecording(actual)),
+            [TAG_WRITE, addr, value, ..] => (Transaction::Write { addr, value }, &record[3..3]),
+            [TAG_READ, addr, len, ..] if record.len() >= 3 + len as usize => (
+                Transaction::Read {
+                    addr,
+                    len: len as usize,
+                },
+                &record[3..3 + len as usize],
+            ),
+            [TAG_WRITE_MULTIPLE, addr, len, ..] if record.len() >= 3 + len as usize => {
+                let data = &record[3..3 + len as usize];
+                (Transaction::write_multiple(addr, data), data)
+            }
+            _ => return Err(ReplayError::Corrupt(offset)),
+        };
+        // the bytes of a multiple byte write are checked by the caller
+        let same = match (expected, actual) {
+            (
+                Transaction::WriteMultiple { addr, len, .. },
+                Transaction::WriteMultiple {
+                    addr: actual_addr,
+                    len: actual_len,
+                    ..
+                },
+            ) => addr == actual_addr && len == actual_len,
+            _ => expected == actual,
+        };
+        if !same {
+            return Err(ReplayError::Mismatch {
+                offset,
+                expected,
+                actual,
+            });
+        }
+        self.position += 3 + data.len();
+        Ok(data)
+    }
+}
+
+impl<'a> Interface for ReplayInterface<'a> {
+    type Error = ReplayError;
+
+    fn write(&mut self, addr: u8, value: u8) -> Result<(), Self::Error> {
+        self.next(Transaction::Write { addr, value })?;
+        Ok(())
+    }
+
+    fn read(&mut self, addr: u8, buffer: &mut [u8]) -> Result<(), Self::Error> {
+        let data = self.next(Transaction::Read {
+            addr,
+            len: buffer.len(),
+        })?;
+        buffer.copy_from_slice(data);
+        Ok(())
+    }
+
+    fn write_multiple(&mut self, addr: u8, values: &[u8]) -> Result<(), Self::Error> {
+        let offset = self.position;
+        let data = self.next(Transaction::write_multiple(addr, values))?;
+        if data != values {
+            self.position = offset;
+            return Err(ReplayError::DataMismatch(offset));
+        }
+        Ok(())
+    }
+}
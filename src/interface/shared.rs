@@ -0,0 +1,125 @@
+//! Shared I2C bus support
+//!
+//! [`SharedI2c`] is a handle to an I2C bus shared by several drivers: it
+//! implements the `embedded-hal` I2C traits by locking the bus for each
+//! transaction, so it can be passed to `I2cInterface::init` as well as to any
+//! other driver. The bus can be held in:
+//! - a `RefCell`, for drivers used from a single execution context
+//! - a `critical_section::Mutex<RefCell<_>>` (feature `critical-section`), for
+//!   drivers used from interrupt handlers too
+//! - any other mutex, by implementing [`BusMutex`] for it
+//!
+//! [`BorrowedI2c`] wraps a `&mut I2C` instead, for applications that own the
+//! bus and hand it to the driver for each call: a driver created with
+//! `LPS22HB::detached` holds [`NoBus`] and is lent the bus with `with_bus` or
+//! `attach`, keeping its state between the calls.
+//!
+//! ```rust,ignore
+//! let bus = RefCell::new(i2c1);
+//! let mut lps22 = LPS22HB::new(I2cInterface::init(SharedI2c::new(&bus), I2cAddress::SA0_GND));
+//! let mut other = OtherDriver::new(SharedI2c::new(&bus));
+//! ```
+
+use core::cell::RefCell;
+use embedded_hal::blocking::i2c::{Write, WriteRead};
+
+/// Mutex protecting a shared bus
+pub trait BusMutex {
+    /// Bus type
+    type Bus;
+    /// Run `f` with exclusive access to the bus
+    fn lock<R, F: FnOnce(&mut Self::Bus) -> R>(&self, f: F) -> R;
+}
+
+impl<B> BusMutex for RefCell<B> {
+    type Bus = B;
+
+    fn lock<R, F: FnOnce(&mut B) -> R>(&self, f: F) -> R {
+        f(&mut self.borrow_mut())
+    }
+}
+
+#[cfg(feature = "critical-section")]
+impl<B> BusMutex for critical_section::Mutex<RefCell<B>> {
+    type Bus = B;
+
+    fn lock<R, F: FnOnce(&mut B) -> R>(&self, f: F) -> R {
+        critical_section::with(|cs| f(&mut self.borrow_ref_mut(cs)))
+    }
+}
+
+/// Handle to an I2C bus shared through a [`BusMutex`]
+#[derive(Debug)]
+pub struct SharedI2c<'a, M> {
+    bus: &'a M,
+}
+
+impl<'a, M: BusMutex> SharedI2c<'a, M> {
+    /// Create a handle to the shared `bus`
+    pub fn new(bus: &'a M) -> Self {
+        SharedI2c { bus }
+    }
+}
+
+impl<'a, M> Clone for SharedI2c<'a, M> {
+    fn clone(&self) -> Self {
+        SharedI2c { bus: self.bus }
+    }
+}
+
+impl<'a, M, E> Write for SharedI2c<'a, M>
+where
+    M: BusMutex,
+    M::Bus: Write<Error = E>,
+{
+    type Error = E;
+
+    fn write(&mut self, address: u8, bytes: &[u8]) -> Result<(), E> {
+        self.bus.lock(|bus| bus.write(address, bytes))
+    }
+}
+
+impl<'a, M, E> WriteRead for SharedI2c<'a, M>
+where
+    M: BusMutex,
+    M::Bus: WriteRead<Error = E>,
+{
+    type Error = E;
+
+    fn write_read(&mut self, address: u8, bytes: &[u8], buffer: &mut [u8]) -> Result<(), E> {
+        self.bus.lock(|bus| bus.write_read(address, bytes, buffer))
+    }
+}
+
+/// I2C bus borrowed for the lifetime of the driver instance
+#[derive(Debug)]
+pub struct BorrowedI2c<'a, I2C> {
+    bus: &'a mut I2C,
+}
+
+impl<'a, I2C> BorrowedI2c<'a, I2C> {
+    /// Borrow `bus`
+    pub fn new(bus: &'a mut I2C) -> Self {
+        BorrowedI2c { bus }
+    }
+}
+
+impl<'a, I2C: Write> Write for BorrowedI2c<'a, I2C> {
+    type Error = I2C::Error;
+
+    fn write(&mut self, address: u8, bytes: &[u8]) -> Result<(), Self::Error> {
+        self.bus.write(address, bytes)
+    }
+}
+
+impl<'a, I2C: WriteRead> WriteRead for BorrowedI2c<'a, I2C> {
+    type Error = I2C::Error;
+
+    fn write_read(&mut self, address: u8, bytes: &[u8], buffer: &mut [u8]) -> Result<(), Self::Error> {
+        self.bus.write_read(address, bytes, buffer)
+    }
+}
+
+/// Bus of a detached driver instance, which is lent the I2C bus for each access
+#[derive(Debug, Clone, Copy)]
+pub struct NoBus;
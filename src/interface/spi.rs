@@ -1,6 +1,6 @@
 //! SPI Interface
-
-// DOESN'T WORK RIGHT, PROBLEMS WITH MULTIBYTE READING
+//!
+//! Experimental: the read and write framing is checked against a mock bus, not on hardware yet.
 
 use super::Interface;
 use embedded_hal::{blocking::spi::Transfer, blocking::spi::Write, digital::v2::OutputPin};
@@ -63,5 +63,14 @@ where
         self.cs.set_high().map_err(Error::Pin)?;        
         Ok(())
     }
+
+    fn write_multiple(&mut self, addr: u8, values: &[u8]) -> Result<(), Self::Error> {
+        // address with the R/W bit low, then the data, in one CS-low frame
+        self.cs.set_low().map_err(Error::Pin)?;
+        self.spi.write(&[addr & !SPI_READ]).map_err(Error::Comm)?;
+        self.spi.write(values).map_err(Error::Comm)?;
+        self.cs.set_high().map_err(Error::Pin)?;
+        Ok(())
+    }
 }
  
\ No newline at end of file
@@ -0,0 +1,299 @@
+//! Bus tracing wrapper for diagnostics
+//!
+//! [`TracingInterface`] wraps any `Interface` and reports every transaction
+//! to a [`TraceSink`]: a fixed-size [`TraceBuffer`], a closure, or the `log`
+//! and `defmt` frameworks (with the `log` and `defmt` features).
+//!
+//! ```rust,ignore
+//! let trace = TracingInterface::new(I2cInterface::init(i2c, I2cAddress::SA0_VCC), TraceBuffer::<16>::new());
+//! let mut lps22 = LPS22HB::new(trace);
+//! lps22.one_shot().unwrap();
+//! let (interface, buffer) = lps22.destroy().destroy();
+//! for transaction in buffer.iter() {
+//!     // W CTRL_REG1 (0x10) <- 0x00, R CTRL_REG2 (0x11) x1, W CTRL_REG2 (0x11) <- 0x11 ...
+//!     // multiple byte writes show their bytes: W REF_P_XL (0x15) <- 0x00 0xe0 0x3d
+//!     println!("{}", transaction);
+//! }
+//! ```
+
+use super::Interface;
+use crate::register::Registers;
+use core::fmt;
+
+/// Number of bytes of a multiple byte write kept in a [`Transaction`],
+/// the size of the largest register (REF_P)
+pub const MAX_TRACED_BYTES: usize = 3;
+
+/// A single bus transaction
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Transaction {
+    /// Single byte written to a register
+    Write {
+        /// Register address
+        addr: u8,
+        /// Value written
+        value: u8,
+    },
+    /// Bytes written to consecutive registers in one transaction
+    WriteMultiple {
+        /// Address of the first register
+        addr: u8,
+        /// Number of bytes written
+        len: usize,
+        /// First bytes written (at most `MAX_TRACED_BYTES`), followed by zeros
+        values: [u8; MAX_TRACED_BYTES],
+    },
+    /// Bytes read starting at a register
+    Read {
+        /// Address of the first register
+        addr: u8,
+        /// Number of bytes read
+        len: usize,
+    },
+}
+
+impl Transaction {
+    /// Multiple byte write of `values` starting at `addr`
+    pub fn write_multiple(addr: u8, values: &[u8]) -> Self {
+        let mut traced = [0; MAX_TRACED_BYTES];
+        let n = values.len().min(MAX_TRACED_BYTES);
+        traced[..n].copy_from_slice(&values[..n]);
+        Transaction::WriteMultiple {
+            addr,
+            len: values.len(),
+            values: traced,
+        }
+    }
+
+    /// Bytes written (only the first `MAX_TRACED_BYTES` of a multiple byte write), empty for a read
+    pub fn values(&self) -> &[u8] {
+        match self {
+            Transaction::Write { value, .. } => core::slice::from_ref(value),
+            Transaction::WriteMultiple { len, values, .. } => {
+                &values[..(*len).min(MAX_TRACED_BYTES)]
+            }
+            Transaction::Read { .. } => &[],
+        }
+    }
+
+    /// Address of the (first) register accessed
+    pub fn addr(self) -> u8 {
+        match self {
+            Transaction::Write { addr, .. }
+            | Transaction::WriteMultiple { addr, .. }
+            | Transaction::Read { addr, .. } => addr,
+        }
+    }
+
+    /// Register accessed, `None` for a reserved address
+    pub fn register(self) -> Option<Registers> {
+        Registers::from_addr(self.addr())
+    }
+
+    /// Name of the register accessed
+    fn name(self) -> &'static str {
+        self.register().map_or("reserved", Registers::name)
+    }
+}
+
+impl fmt::Display for Transaction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            Transaction::Write { addr, value } => {
+                write!(f, "W {} ({:#04x}) <- {:#04x}", self.name(), addr, value)
+            }
+            Transaction::WriteMultiple { addr, len, .. } => {
+                write!(f, "W {} ({:#04x}) <-", self.name(), addr)?;
+                for value in self.values() {
+                    write!(f, " {:#04x}", value)?;
+                }
+                if len > MAX_TRACED_BYTES {
+                    write!(f, " .. x{}", len)?;
+                }
+                Ok(())
+            }
+            Transaction::Read { addr, len } => {
+                write!(f, "R {} ({:#04x}) x{}", self.name(), addr, len)
+            }
+        }
+    }
+}
+
+#[cfg(feature = "defmt")]
+impl defmt::Format for Transaction {
+    fn format(&self, f: defmt::Formatter) {
+        match *self {
+            Transaction::Write { addr, value } => {
+                defmt::write!(f, "W {=str} ({=u8:#04x}) <- {=u8:#04x}", self.name(), addr, value)
+            }
+            Transaction::WriteMultiple { addr, len, .. } => {
+                defmt::write!(f, "W {=str} ({=u8:#04x}) <-", self.name(), addr);
+                for value in self.values() {
+                    defmt::write!(f, " {=u8:#04x}", value);
+                }
+                if len > MAX_TRACED_BYTES {
+                    defmt::write!(f, " .. x{=usize}", len);
+                }
+            }
+            Transaction::Read { addr, len } => {
+                defmt::write!(f, "R {=str} ({=u8:#04x}) x{=usize}", self.name(), addr, len)
+            }
+        }
+    }
+}
+
+/// Destination of the traced transactions
+pub trait TraceSink {
+    /// Record a transaction, called before it is performed
+    fn record(&mut self, transaction: Transaction);
+}
+
+impl<F: FnMut(Transaction)> TraceSink for F {
+    fn record(&mut self, transaction: Transaction) {
+        self(transaction)
+    }
+}
+
+/// Ring buffer keeping the last `N` transactions
+#[derive(Debug, Clone)]
+pub struct TraceBuffer<const N: usize> {
+    entries: [Option<Transaction>; N],
+    index: usize,
+    count: usize,
+    dropped: usize,
+}
+
+impl<const N: usize> TraceBuffer<N> {
+    /// Create an empty buffer
+    pub fn new() -> Self {
+        TraceBuffer {
+            entries: [None; N],
+            index: 0,
+            count: 0,
+            dropped: 0,
+        }
+    }
+
+    /// Number of transactions stored
+    pub fn len(&self) -> usize {
+        self.count
+    }
+
+    /// Is the buffer empty?
+    pub fn is_empty(&self) -> bool {
+        self.count == 0
+    }
+
+    /// Number of transactions overwritten because the buffer was full
+    pub fn dropped(&self) -> usize {
+        self.dropped
+    }
+
+    /// Remove all transactions
+    pub fn clear(&mut self) {
+        *self = Self::new();
+    }
+
+    /// Stored transactions, oldest first
+    pub fn iter(&self) -> impl Iterator<Item = &Transaction> + '_ {
+        let start = (self.index + N - self.count) % N.max(1);
+        (0..self.count).filter_map(move |i| self.entries[(start + i) % N].as_ref())
+    }
+}
+
+impl<const N: usize> Default for TraceBuffer<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const N: usize> TraceSink for TraceBuffer<N> {
+    fn record(&mut self, transaction: Transaction) {
+        if N == 0 {
+            self.dropped += 1;
+            return;
+        }
+        if self.count == N {
+            self.dropped += 1;
+        } else {
+            self.count += 1;
+        }
+        self.entries[self.index] = Some(transaction);
+        self.index = (self.index + 1) % N;
+    }
+}
+
+/// Sends the transactions to the `log` framework at debug level
+#[cfg(feature = "log")]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct LogSink;
+
+#[cfg(feature = "log")]
+impl TraceSink for LogSink {
+    fn record(&mut self, transaction: Transaction) {
+        log::debug!("lps22hb: {}", transaction);
+    }
+}
+
+/// Sends the transactions to `defmt` at debug level
+#[cfg(feature = "defmt")]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct DefmtSink;
+
+#[cfg(feature = "defmt")]
+impl TraceSink for DefmtSink {
+    fn record(&mut self, transaction: Transaction) {
+        defmt::debug!("lps22hb: {}", transaction);
+    }
+}
+
+/// Interface wrapper reporting every transaction to a [`TraceSink`]
+#[derive(Debug)]
+pub struct TracingInterface<I, S> {
+    interface: I,
+    sink: S,
+}
+
+impl<I: Interface, S: TraceSink> TracingInterface<I, S> {
+    /// Wrap `interface`, sending its transactions to `sink`
+    pub fn new(interface: I, sink: S) -> Self {
+        TracingInterface { interface, sink }
+    }
+
+    /// Return the wrapped interface and the sink
+    pub fn destroy(self) -> (I, S) {
+        (self.interface, self.sink)
+    }
+
+    /// The sink, e.g. to inspect a `TraceBuffer`
+    pub fn sink(&self) -> &S {
+        &self.sink
+    }
+
+    /// Mutable access to the sink, e.g. to clear a `TraceBuffer`
+    pub fn sink_mut(&mut self) -> &mut S {
+        &mut self.sink
+    }
+}
+
+impl<I: Interface, S: TraceSink> Interface for TracingInterface<I, S> {
+    type Error = I::Error;
+
+    fn write(&mut self, addr: u8, value: u8) -> Result<(), Self::Error> {
+        self.sink.record(Transaction::Write { addr, value });
+        self.interface.write(addr, value)
+    }
+
+    fn write_multiple(&mut self, addr: u8, values: &[u8]) -> Result<(), Self::Error> {
+        self.sink.record(Transaction::write_multiple(addr, values));
+        self.interface.write_multiple(addr, values)
+    }
+
+    fn read(&mut self, addr: u8, buffer: &mut [u8]) -> Result<(), Self::Error> {
+        self.sink.record(Transaction::Read {
+            addr,
+            len: buffer.len(),
+        });
+        self.interface.read(addr, buffer)
+    }
+}
@@ -102,9 +102,10 @@ pub struct IntStatus {
     pub diff_press_high: bool,    
 }
 
-impl<T, E> LPS22HB<T>
+impl<T, E, MODE> LPS22HB<T, MODE>
 where
     T: Interface<Error = E>,
+    MODE: Configurable,
 {
     /// Enable interrupts and configure the interrupt pin
     pub fn configure_interrupts(&mut self, 
@@ -113,45 +114,37 @@ where
                                  -> Result<(), T::Error> {        
         
         // write the whole CTRL_REG3 register                                    
-        self.interface.write(Registers::CTRL_REG3.addr(), config.int_ctrl_reg3())?;                
+        self.write_register(Registers::CTRL_REG3, config.int_ctrl_reg3())?;                
         
         // get the contents of INTERRUPT_CFG and combine it with the bits to be set
-        let mut reg_data = [0u8;1];
-        self.read_register(Registers::INTERRUPT_CFG)?;        
-                
-        let mut interrupt_cfg = config.int_interrupt_cfg();
-               
-        let mut data: u8 = reg_data[0] & !0b00001111;
+        let reg_data = self.read_register(Registers::INTERRUPT_CFG)?;
+
+        let interrupt_cfg = config.int_interrupt_cfg();
 
-        data = data | interrupt_cfg;
+        let data: u8 = (reg_data & !0b00001111) | interrupt_cfg;
 
-        self.interface.write(Registers::INTERRUPT_CFG.addr(), data)?;        
+        self.write_register(Registers::INTERRUPT_CFG, data)?;        
         
         Ok(())
     }
-    
-    
+}
+
+impl<T, E, MODE> LPS22HB<T, MODE>
+where
+    T: Interface<Error = E>,
+{
  /// Get all the flags from the INT_SOURCE register (NOTE: INT_SOURCE register is cleared by reading it)
  pub fn get_int_status(&mut self) -> Result<IntStatus, T::Error> {        
                 
     let reg_value = self.read_register(Registers::INT_SOURCE)?;
 
     let status = IntStatus {
-        /// Has any interrupt event been generated?
-        interrupt_active: match reg_value & Bitmasks::IA {
-            0 => false,
-            _ => true,
-        },
-        /// Has low differential pressure event been generated?
-        diff_press_low: match reg_value & Bitmasks::PL {
-            0 => false,
-            _ => true,
-        },
-        /// Has high differential pressure event been generated?
-        diff_press_high: match reg_value & Bitmasks::PH {
-            0 => false,
-            _ => true,
-        },           
+        // Has any interrupt event been generated?
+        interrupt_active: reg_value & Bitmasks::IA != 0,
+        // Has low differential pressure event been generated?
+        diff_press_low: reg_value & Bitmasks::PL != 0,
+        // Has high differential pressure event been generated?
+        diff_press_high: reg_value & Bitmasks::PH != 0,
     };
     Ok(status)
  }
@@ -0,0 +1,128 @@
+//! Leak detection for sealed enclosures
+//!
+//! In a rigid sealed volume the ratio between pressure and absolute temperature
+//! stays constant (P/T ≈ constant). The detector learns that ratio from the
+//! pressure and temperature channels of the sensor, then flags a leak when the
+//! ratio drifts beyond a tolerance, i.e. when the pressure starts tracking the
+//! ambient pressure instead of the temperature.
+
+use altitude::ZERO_CELSIUS;
+use sensor::Measurement;
+
+use super::*;
+
+/// Leak detector settings
+#[derive(Debug, Clone, Copy)]
+pub struct LeakDetectorConfig {
+    /// Number of samples averaged to learn the sealed P/T ratio
+    pub learning_samples: u16,
+    /// Largest accepted relative deviation from the learned ratio (0.005 = 0.5 %)
+    pub tolerance: f32,
+    /// Smoothing factor of the monitored ratio (0 < smoothing <= 1, smaller is smoother)
+    pub smoothing: f32,
+    /// Number of consecutive samples beyond the tolerance needed to report a leak
+    pub confirm_samples: u16,
+}
+
+impl Default for LeakDetectorConfig {
+    fn default() -> Self {
+        LeakDetectorConfig {
+            learning_samples: 60,
+            tolerance: 0.005, // about 5 hPa at sea level
+            smoothing: 0.1,
+            confirm_samples: 10,
+        }
+    }
+}
+
+/// Result of a leak check
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LeakStatus {
+    /// Still learning the sealed ratio, number of samples collected so far
+    Learning(u16),
+    /// Enclosure sealed, relative deviation from the learned ratio
+    Sealed(f32),
+    /// Leak detected, relative deviation from the learned ratio
+    Leak(f32),
+}
+
+/// Sealed enclosure leak detector
+#[derive(Debug, Clone)]
+pub struct LeakDetector {
+    config: LeakDetectorConfig,
+    learned: Option<f32>,
+    sum: f32,
+    count: u16,
+    ratio: f32,
+    beyond: u16,
+}
+
+impl LeakDetector {
+    /// Create a detector that starts by learning the sealed ratio
+    pub fn new(config: LeakDetectorConfig) -> Self {
+        LeakDetector {
+            config,
+            learned: None,
+            sum: 0.0,
+            count: 0,
+            ratio: 0.0,
+            beyond: 0,
+        }
+    }
+
+    /// Create a detector with a ratio learned earlier (e.g. stored at production)
+    pub fn with_ratio(config: LeakDetectorConfig, ratio: f32) -> Self {
+        LeakDetector {
+            learned: Some(ratio),
+            ratio,
+            ..Self::new(config)
+        }
+    }
+
+    /// Forget the learned ratio and start learning again (e.g. after resealing)
+    pub fn relearn(&mut self) {
+        *self = Self::new(self.config);
+    }
+
+    /// Learned P/T ratio in hPa/K
+    pub fn learned_ratio(&self) -> Option<f32> {
+        self.learned
+    }
+
+    /// Pressure (hPa) expected in the sealed enclosure at the given temperature (°C)
+    pub fn expected_pressure(&self, temperature: f32) -> Option<f32> {
+        self.learned.map(|ratio| ratio * (temperature + ZERO_CELSIUS))
+    }
+
+    /// Feed a pressure and temperature sample (e.g. from `read_measurement()`)
+    pub fn update(&mut self, sample: Measurement) -> LeakStatus {
+        let ratio = sample.pressure / (sample.temperature + ZERO_CELSIUS);
+        let learned = match self.learned {
+            Some(learned) => learned,
+            None => {
+                self.sum += ratio;
+                self.count += 1;
+                if self.count < self.config.learning_samples {
+                    return LeakStatus::Learning(self.count);
+                }
+                let learned = self.sum / self.count as f32;
+                self.learned = Some(learned);
+                self.ratio = learned;
+                return LeakStatus::Sealed(0.0);
+            }
+        };
+
+        self.ratio += self.config.smoothing * (ratio - self.ratio);
+        let deviation = self.ratio / learned - 1.0;
+        if libm::fabsf(deviation) > self.config.tolerance {
+            self.beyond = self.beyond.saturating_add(1);
+        } else {
+            self.beyond = 0;
+        }
+        if self.beyond >= self.config.confirm_samples {
+            LeakStatus::Leak(deviation)
+        } else {
+            LeakStatus::Sealed(deviation)
+        }
+    }
+}
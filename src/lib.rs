@@ -5,13 +5,16 @@
 //! - read temperature in degrees Celsius, see [`read_temperature()`]
 //! - enable single-shot data acquisition, see [`enable_one_shot()`]
 //! - set data rate, see [`set_datarate()`]
+//! - average several readings with statistics, see [`measure_averaged()`]
 //!
 //! [`read_pressure()`]: struct.LPS22HB.html#method.read_pressure
 //! [`read_temperature()`]: struct.LPS22HB.html#method.read_temperature
 //! [`enable_one_shot()`]: struct.LPS22HB.html#method.enable_one_shot
 //! [`set_datarate()`]: struct.LPS22HB.html#method.set_datarate
+//! [`measure_averaged()`]: struct.LPS22HB.html#method.measure_averaged
 //!
-//! __NOTE__: Only I2C interface is supported at the moment.
+//! __NOTE__: the I2C interface is supported. The SPI interface ([`interface::SpiInterface`]) is
+//! experimental: its framing is only checked against a mock bus, not on hardware yet.
 //!  //!
 //! ### Datasheet: [LPS22HB](https://www.st.com/resource/en/datasheet/lps22hb.pdf)
 //!
@@ -23,7 +26,7 @@
 //!
 //! ### Read pressure and temperature
 //!
-//! ```rust
+//! ```rust,ignore
 //!
 //! use lps22hb::interface::{I2cInterface, i2c::I2cAddress};
 //! use lps22hb::*;
@@ -40,6 +43,9 @@
 #![no_std]
 //#![deny(warnings, missing_docs)]
 
+use core::convert::TryFrom;
+use core::marker::PhantomData;
+
 pub mod sensor;
 //use sensor::*;
 
@@ -52,6 +58,59 @@ pub mod fifo;
 pub mod interrupt;
 //use interrupt::*;
 
+pub mod average;
+//use average::*;
+
+pub mod filter;
+//use filter::*;
+
+pub mod altitude;
+//use altitude::*;
+
+pub mod variometer;
+//use variometer::*;
+
+pub mod reduction;
+//use reduction::*;
+
+pub mod weather;
+//use weather::*;
+
+pub mod storm;
+//use storm::*;
+
+pub mod floor;
+//use floor::*;
+
+pub mod flight;
+//use flight::*;
+
+pub mod leak;
+//use leak::*;
+
+pub mod dual;
+//use dual::*;
+
+pub mod preset;
+//use preset::*;
+
+pub mod power;
+//use power::*;
+
+pub mod dump;
+//use dump::*;
+
+pub mod snapshot;
+//use snapshot::*;
+
+pub mod shadow;
+use shadow::Shadow;
+//use shadow::*;
+
+pub mod typestate;
+use typestate::{Configurable, Untyped};
+//use typestate::*;
+
 pub mod register;
 use register::{Bitmasks, Registers};
 //use register::*;
@@ -59,17 +118,25 @@ use register::{Bitmasks, Registers};
 pub mod interface;
 use interface::Interface;
 
-/// Sensor's ID
+#[cfg(feature = "sim")]
+pub mod sim;
+
+// Sensor's ID
 //const WHOAMI: u8 = 0b10110001; // decimal value 177
 
 /// The output of the temperature sensor must be divided by 100, see p. 10 of the datasheet.
 const TEMP_SCALE: f32 = 100.0;
 /// The output of the pressure sensor must be divided by 4096, see p. 10 of the datasheet.
 const PRESS_SCALE: f32 = 4096.0;
+/// The pressure offset (RPDS) and threshold (THS_P) registers have a resolution of 1/16 hPa.
+const OFFSET_SCALE: f32 = 16.0;
 
 /// Holds the driver instance with the selected interface
-pub struct LPS22HB<T> {
+/// (`MODE` is the power mode for the typed API, see the `typestate` module)
+pub struct LPS22HB<T, MODE = Untyped> {
     interface: T,
+    shadow: Shadow,
+    mode: PhantomData<MODE>,
 }
 
 impl<T, E> LPS22HB<T>
@@ -78,14 +145,32 @@ where
 {
     /// Create a new instance of the LPS25HB driver.
     pub fn new(interface: T) -> Self {
-        LPS22HB { interface }
+        LPS22HB {
+            interface,
+            shadow: Shadow::new(),
+            mode: PhantomData,
+        }
     }
+}
 
+impl<T, E, MODE> LPS22HB<T, MODE>
+where
+    T: Interface<Error = E>,
+{
     /// Destroy driver instance, return interface instance.
     pub fn destroy(self) -> T {
         self.interface
     }
 
+    /// Same driver instance in another power mode
+    fn into_mode<NEW>(self) -> LPS22HB<T, NEW> {
+        LPS22HB {
+            interface: self.interface,
+            shadow: self.shadow,
+            mode: PhantomData,
+        }
+    }
+
     /*
     /// Verifies communication with WHO_AM_I register
     pub fn sensor_is_reachable(&mut self) -> Result<bool, T::Error> {
@@ -109,29 +194,86 @@ where
     }
     */
 
-    /// Read a byte from the given register.
+    /// Read a byte from the given register (from the shadow copy when available).
     fn read_register(&mut self, address: Registers) -> Result<u8, T::Error> {
+        if let Some(value) = self.shadow.get(address) {
+            return Ok(value);
+        }
         let mut reg_data = [0u8];
         self.interface.read(address.addr(), &mut reg_data)?;
+        self.shadow.store(address, reg_data[0]);
         Ok(reg_data[0])
     }
 
+    /// Write a byte to the given register, keeping the shadow copy up to date.
+    fn write_register(&mut self, address: Registers, value: u8) -> Result<(), T::Error> {
+        self.interface.write(address.addr(), value)?;
+        if address == Registers::CTRL_REG2 && value & (Bitmasks::BOOT | Bitmasks::SWRESET) != 0 {
+            // the device reloads its registers, a software reset restores the CTRL_REG2 default
+            self.shadow.invalidate();
+            match value & Bitmasks::SWRESET {
+                0 => self.shadow.store(address, value),
+                _ => self.shadow.store(address, Bitmasks::IF_ADD_INC),
+            }
+        } else {
+            self.shadow.store(address, value);
+        }
+        Ok(())
+    }
+
+    /// Read consecutive registers, in a single transaction when the address auto-increment
+    /// (IF_ADD_INC) is enabled, one register at a time otherwise. The shadow copy is not used.
+    fn read_block(&mut self, first: Registers, values: &mut [u8]) -> Result<(), T::Error> {
+        if self.shadow.auto_increment() {
+            return self.interface.read(first.addr(), values);
+        }
+        for (i, value) in values.iter_mut().enumerate() {
+            self.interface
+                .read(first.addr() + i as u8, core::slice::from_mut(value))?;
+        }
+        Ok(())
+    }
+
+    /// Read consecutive registers (see `read_block`), keeping the shadow copy up to date.
+    fn read_registers(&mut self, first: Registers, values: &mut [u8]) -> Result<(), T::Error> {
+        self.read_block(first, values)?;
+        for (i, value) in values.iter().enumerate() {
+            if let Some(register) = Registers::from_addr(first.addr() + i as u8) {
+                self.shadow.store(register, *value);
+            }
+        }
+        Ok(())
+    }
+
+    /// Write consecutive registers, in a single transaction when the address auto-increment
+    /// (IF_ADD_INC) is enabled, one register at a time otherwise.
+    /// Keeps the shadow copy up to date.
+    fn write_registers(&mut self, first: Registers, values: &[u8]) -> Result<(), T::Error> {
+        if self.shadow.auto_increment() {
+            self.interface.write_multiple(first.addr(), values)?;
+        } else {
+            for (i, value) in values.iter().enumerate() {
+                self.interface.write(first.addr() + i as u8, *value)?;
+            }
+        }
+        for (i, value) in values.iter().enumerate() {
+            if let Some(register) = Registers::from_addr(first.addr() + i as u8) {
+                self.shadow.store(register, *value);
+            }
+        }
+        Ok(())
+    }
+
     /// Clear selected bits using a bitmask
     fn clear_register_bit_flag(&mut self, address: Registers, bitmask: u8) -> Result<(), T::Error> {
-        let mut reg_data = [0u8; 1];
-        self.interface.read(address.addr(), &mut reg_data)?;        
-        let payload: u8 = reg_data[0] & !bitmask;
-        self.interface.write(address.addr(), payload)?;
-        Ok(())
+        let payload: u8 = self.read_register(address)? & !bitmask;
+        self.write_register(address, payload)
     }
 
     /// Set selected bits using a bitmask
     fn set_register_bit_flag(&mut self, address: Registers, bitmask: u8) -> Result<(), T::Error> {
-        let mut reg_data = [0u8; 1];
-        self.interface.read(address.addr(), &mut reg_data)?;
-        let payload: u8 = reg_data[0] | bitmask;
-        self.interface.write(address.addr(), payload)?;
-        Ok(())
+        let payload: u8 = self.read_register(address)? | bitmask;
+        self.write_register(address, payload)
     }
 
     /// Check if specific bits are set.
@@ -145,9 +287,13 @@ where
     }
 }
 
+/// Reserved bit combination found while decoding a register field
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct InvalidFieldValue(pub u8);
+
 /// Output data rate and power mode selection (ODR). (Refer to Table 17)
 #[allow(non_camel_case_types)]
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum ODR {
     /// Power-down / One-shot mode enabled
     PowerDown = 0b000,
@@ -167,6 +313,114 @@ impl ODR {
     pub fn value(self) -> u8 {
         (self as u8) << 4
     }
+
+    /// Output data rate in Hz, `None` in power-down mode
+    pub fn hz(self) -> Option<f32> {
+        match self {
+            ODR::PowerDown => None,
+            ODR::_1Hz => Some(1.0),
+            ODR::_10Hz => Some(10.0),
+            ODR::_25Hz => Some(25.0),
+            ODR::_50Hz => Some(50.0),
+            ODR::_75Hz => Some(75.0),
+        }
+    }
+
+    /// Sample period in seconds, `None` in power-down mode
+    pub fn period(self) -> Option<f32> {
+        match self {
+            ODR::PowerDown => None,
+            ODR::_1Hz => Some(1.0),
+            ODR::_10Hz => Some(1.0 / 10.0),
+            ODR::_25Hz => Some(1.0 / 25.0),
+            ODR::_50Hz => Some(1.0 / 50.0),
+            ODR::_75Hz => Some(1.0 / 75.0),
+        }
+    }
+
+    /// Supported output data rate nearest to `hz` (the lower one on a tie),
+    /// power-down for a rate of 0 Hz or less
+    pub fn nearest(hz: f32) -> Self {
+        if hz.is_nan() || hz <= 0.0 {
+            return ODR::PowerDown;
+        }
+        let mut nearest = ODR::_1Hz;
+        for odr in [ODR::_10Hz, ODR::_25Hz, ODR::_50Hz, ODR::_75Hz] {
+            if libm::fabsf(odr.hz().unwrap_or(0.0) - hz) < libm::fabsf(nearest.hz().unwrap_or(0.0) - hz) {
+                nearest = odr;
+            }
+        }
+        nearest
+    }
+}
+
+impl TryFrom<u8> for ODR {
+    type Error = InvalidFieldValue;
+
+    /// Decode the ODR bits of a CTRL_REG1 value (the other bits are ignored)
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match (value & Bitmasks::ODR_MASK) >> 4 {
+            0b000 => Ok(ODR::PowerDown),
+            0b001 => Ok(ODR::_1Hz),
+            0b010 => Ok(ODR::_10Hz),
+            0b011 => Ok(ODR::_25Hz),
+            0b100 => Ok(ODR::_50Hz),
+            0b101 => Ok(ODR::_75Hz),
+            field => Err(InvalidFieldValue(field)),
+        }
+    }
+}
+
+/// Power mode selection (RES_CONF.LC_EN)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PowerMode {
+    /// Low-noise mode (LC_EN = 0, default): [`power::LOW_NOISE_RMS`] pressure noise,
+    /// [`power::LOW_NOISE_CURRENT`] at 1 Hz ODR
+    LowNoise,
+    /// Low-current mode (LC_EN = 1): [`power::LOW_CURRENT_CURRENT`] at 1 Hz ODR, at the cost
+    /// of a higher pressure noise, [`power::LOW_CURRENT_RMS`]
+    LowCurrent,
+}
+
+/// Low-pass filter on pressure data (EN_LPFP and LPFP_CFG). (Refer to Table 18)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LowPassFilter {
+    /// Filter disabled, bandwidth ODR/2
+    Disabled,
+    /// Bandwidth ODR/9
+    OdrDiv9,
+    /// Bandwidth ODR/20
+    OdrDiv20,
+}
+
+impl LowPassFilter {
+    /// Decode the EN_LPFP and LPFP_CFG bits of CTRL_REG1
+    pub(crate) fn from_value(value: u8) -> Self {
+        match (value & Bitmasks::EN_LPFP != 0, value & Bitmasks::LPFP_CFG != 0) {
+            (false, _) => LowPassFilter::Disabled,
+            (true, false) => LowPassFilter::OdrDiv9,
+            (true, true) => LowPassFilter::OdrDiv20,
+        }
+    }
+
+    /// EN_LPFP and LPFP_CFG bits of CTRL_REG1
+    pub fn value(self) -> u8 {
+        match self {
+            LowPassFilter::Disabled => 0,
+            LowPassFilter::OdrDiv9 => Bitmasks::EN_LPFP,
+            LowPassFilter::OdrDiv20 => Bitmasks::EN_LPFP | Bitmasks::LPFP_CFG,
+        }
+    }
+
+    /// Cut-off frequency in Hz at the given output data rate, `None` in power-down mode
+    pub fn cutoff_hz(self, odr: ODR) -> Option<f32> {
+        let divider = match self {
+            LowPassFilter::Disabled => 2.0,
+            LowPassFilter::OdrDiv9 => 9.0,
+            LowPassFilter::OdrDiv20 => 20.0,
+        };
+        odr.hz().map(|hz| hz / divider)
+    }
 }
 
 /// SPI interface mode
@@ -181,7 +435,7 @@ pub enum SPI_Mode {
 
 /// FIFO mode selection. (Refer to Table 20)
 #[allow(non_camel_case_types)]
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum FIFO_MODE {
     /// Bypass mode
     Bypass = 0b000,
@@ -205,9 +459,27 @@ impl FIFO_MODE {
     }
 }
 
+impl TryFrom<u8> for FIFO_MODE {
+    type Error = InvalidFieldValue;
+
+    /// Decode the F_MODE bits of a FIFO_CTRL value (the other bits are ignored)
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match (value & Bitmasks::F_MODE_MASK) >> 5 {
+            0b000 => Ok(FIFO_MODE::Bypass),
+            0b001 => Ok(FIFO_MODE::FIFO),
+            0b010 => Ok(FIFO_MODE::Stream),
+            0b011 => Ok(FIFO_MODE::Stream_to_FIFO),
+            0b100 => Ok(FIFO_MODE::Bypass_to_stream),
+            0b110 => Ok(FIFO_MODE::Dynamic_Stream),
+            0b111 => Ok(FIFO_MODE::Bypass_to_FIFO),
+            field => Err(InvalidFieldValue(field)),
+        }
+    }
+}
+
 /// INT_DRDY pin configuration. (Refer to Table 19)
 #[allow(non_camel_case_types)]
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum INT_DRDY {
     /// Data signal (see CTRL_REG4)
     DataSignal = 0b00,
@@ -225,6 +497,21 @@ impl INT_DRDY {
     }
 }
 
+impl TryFrom<u8> for INT_DRDY {
+    type Error = InvalidFieldValue;
+
+    /// Decode the INT_S bits of a CTRL_REG3 value (the other bits are ignored)
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value & Bitmasks::INT_S_MASK {
+            0b00 => Ok(INT_DRDY::DataSignal),
+            0b01 => Ok(INT_DRDY::P_high),
+            0b10 => Ok(INT_DRDY::P_low),
+            0b11 => Ok(INT_DRDY::P_low_or_high),
+            field => Err(InvalidFieldValue(field)),
+        }
+    }
+}
+
 /// Interrupt active setting for the INT_DRDY pin: active high (default) or active low
 #[allow(non_camel_case_types)]
 #[derive(Debug, Clone, Copy)]
@@ -237,11 +524,10 @@ pub enum INT_ACTIVE {
 
 impl INT_ACTIVE {
     pub fn status(self) -> bool {
-        let status = match self {
+        match self {
             INT_ACTIVE::High => false,
             INT_ACTIVE::Low => true,
-        };
-        status
+        }
     }
 }
 
@@ -257,11 +543,10 @@ pub enum INT_PIN {
 
 impl INT_PIN {
     pub fn status(self) -> bool {
-        let status = match self {
+        match self {
             INT_PIN::PushPull => false,
             INT_PIN::OpenDrain => true,
-        };
-        status
+        }
     }
 }
 
@@ -277,11 +562,10 @@ pub enum FLAG {
 
 impl FLAG {
     pub fn status(self) -> bool {
-        let status = match self {
+        match self {
             FLAG::Disabled => false,
             FLAG::Enabled => true,
-        };
-        status
+        }
     }
 }
 
@@ -297,10 +581,9 @@ pub enum FIFO_ON {
 
 impl FIFO_ON {
     pub fn status(self) -> bool {
-        let status = match self {
+        match self {
             FIFO_ON::Disabled => false,
             FIFO_ON::Enabled => true,
-        };
-        status
+        }
     }
 }
\ No newline at end of file
@@ -0,0 +1,121 @@
+//! Rough model estimates of the supply current and pressure noise of a configuration
+//!
+//! Only the constants below are datasheet figures (typical values of Table 2):
+//! the power-down current, the current at 1 Hz ODR and the RMS noise with the
+//! ODR/20 low-pass filter. Everything else is a model, not a datasheet value:
+//! - the current is extrapolated linearly from the 1 Hz figure, assuming each
+//!   conversion draws the same charge; this is not checked against the actual
+//!   current at the higher data rates
+//! - the noise is scaled with the square root of the bandwidth (ODR/2 without
+//!   the filter, ODR/9 or ODR/20 with it)
+//!
+//! Use them to compare configurations in a first power budget; measure the current
+//! of the actual board for battery-life calculations.
+//!
+//! ```rust,ignore
+//! let estimate = lps22.model_estimate_power()?.unwrap();
+//! // or on host, for a configuration that is not applied yet
+//! let estimate = PowerConfig { odr: ODR::_25Hz, ..Default::default() }.model_estimate();
+//! ```
+
+use super::*;
+
+/// Supply current in power-down mode (uA)
+pub const POWER_DOWN_CURRENT: f32 = 1.0;
+/// Supply current at 1 Hz ODR in low-noise mode (uA)
+pub const LOW_NOISE_CURRENT: f32 = 12.0;
+/// Supply current at 1 Hz ODR in low-current mode (uA)
+pub const LOW_CURRENT_CURRENT: f32 = 3.0;
+/// RMS pressure noise in low-noise mode with the ODR/20 low-pass filter (hPa)
+pub const LOW_NOISE_RMS: f32 = 0.0075;
+/// RMS pressure noise in low-current mode with the ODR/20 low-pass filter (hPa)
+pub const LOW_CURRENT_RMS: f32 = 0.015;
+
+/// Settings determining the current consumption and the noise
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PowerConfig {
+    /// Output data rate
+    pub odr: ODR,
+    /// Low-noise or low-current mode
+    pub power_mode: PowerMode,
+    /// Low-pass filter on pressure data
+    pub lowpass_filter: LowPassFilter,
+}
+
+impl Default for PowerConfig {
+    fn default() -> Self {
+        PowerConfig {
+            odr: ODR::PowerDown,                     // power-on defaults
+            power_mode: PowerMode::LowNoise,
+            lowpass_filter: LowPassFilter::Disabled,
+        }
+    }
+}
+
+/// Rough model estimate of the supply current and noise (see the module documentation)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ModelEstimate {
+    /// Average supply current (uA)
+    pub current: f32,
+    /// RMS pressure noise (hPa)
+    pub noise: f32,
+}
+
+impl PowerConfig {
+    /// Model estimate in continuous mode, or between one-shot measurements in power-down mode
+    pub fn model_estimate(&self) -> ModelEstimate {
+        let rate = self.odr.hz().unwrap_or(0.0);
+        let filter = match self.odr {
+            ODR::PowerDown => LowPassFilter::Disabled, // the filter is only active in continuous mode
+            _ => self.lowpass_filter,
+        };
+        self.estimate_at(rate, filter)
+    }
+
+    /// Model estimate with one-shot measurements triggered `rate` times per second
+    /// in power-down mode (the ODR and the filter are ignored)
+    pub fn model_estimate_one_shot(&self, rate: f32) -> ModelEstimate {
+        self.estimate_at(rate, LowPassFilter::Disabled)
+    }
+
+    fn estimate_at(&self, rate: f32, filter: LowPassFilter) -> ModelEstimate {
+        let (current_1hz, noise) = match self.power_mode {
+            PowerMode::LowNoise => (LOW_NOISE_CURRENT, LOW_NOISE_RMS),
+            PowerMode::LowCurrent => (LOW_CURRENT_CURRENT, LOW_CURRENT_RMS),
+        };
+        // bandwidth relative to ODR/20
+        let bandwidth = match filter {
+            LowPassFilter::Disabled => 10.0,
+            LowPassFilter::OdrDiv9 => 20.0 / 9.0,
+            LowPassFilter::OdrDiv20 => 1.0,
+        };
+        // linear in the rate, from the 1 Hz figure
+        ModelEstimate {
+            current: POWER_DOWN_CURRENT + (current_1hz - POWER_DOWN_CURRENT) * rate,
+            noise: noise * libm::sqrtf(bandwidth),
+        }
+    }
+}
+
+impl<T, E, MODE> LPS22HB<T, MODE>
+where
+    T: Interface<Error = E>,
+{
+    /// Read the settings determining the current consumption and the noise, `None` for a reserved ODR
+    pub fn get_power_config(&mut self) -> Result<Option<PowerConfig>, T::Error> {
+        let odr = self.get_datarate()?;
+        let power_mode = self.get_power_mode()?;
+        let lowpass_filter = self.get_lowpass_filter()?;
+        Ok(odr.map(|odr| PowerConfig {
+            odr,
+            power_mode,
+            lowpass_filter,
+        }))
+    }
+
+    /// Model estimate of the supply current and noise of the current configuration,
+    /// `None` for a reserved ODR
+    pub fn model_estimate_power(&mut self) -> Result<Option<ModelEstimate>, T::Error> {
+        Ok(self.get_power_config()?.map(|config| config.model_estimate()))
+    }
+}
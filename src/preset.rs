@@ -0,0 +1,124 @@
+//! Configuration presets for common applications
+//!
+//! Each preset combines the output data rate, the power mode, the low-pass
+//! filter, block data update and the FIFO as recommended in ST's application
+//! notes, and can be applied in one call with `apply_preset`. `get_preset`
+//! reads the settings back and tells which preset, if any, they match.
+//!
+//! The supply current and noise of each power mode are given by the constants
+//! of the [`power`] module; the current grows with the ODR, see the rough model of
+//! [`power::PowerConfig::model_estimate`].
+
+use super::*;
+use fifo::FIFOConfig;
+
+/// Recommended settings for an application profile
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Preset {
+    /// Weather station: one-shot measurements triggered by the application (typically every second
+    /// or less often) in low-current mode, no filter.
+    /// Lowest current ([`power::LOW_CURRENT_CURRENT`]), noise higher than in low-noise mode,
+    /// which the slow weather changes tolerate; the reading is available one conversion after `one_shot`.
+    WeatherMonitoring,
+    /// Indoor navigation (floor detection): continuous 25 Hz in low-noise mode with the ODR/20 filter
+    /// (1.25 Hz bandwidth) and block data update.
+    /// Lowest noise ([`power::LOW_NOISE_RMS`], about 6 cm of altitude) at a moderate current;
+    /// the filter delays steps by about one second.
+    IndoorNavigation,
+    /// Drones and fast vertical motion: continuous 75 Hz in low-noise mode with the ODR/9 filter
+    /// (8.3 Hz bandwidth), block data update and the FIFO in Stream mode.
+    /// Highest current, short filter latency; the FIFO holds the last 32 samples (0.43 s) so
+    /// the MCU can read them in bursts.
+    Drone,
+}
+
+impl Preset {
+    /// Output data rate
+    pub fn odr(self) -> ODR {
+        match self {
+            Preset::WeatherMonitoring => ODR::PowerDown,
+            Preset::IndoorNavigation => ODR::_25Hz,
+            Preset::Drone => ODR::_75Hz,
+        }
+    }
+
+    /// Power mode
+    pub fn power_mode(self) -> PowerMode {
+        match self {
+            Preset::WeatherMonitoring => PowerMode::LowCurrent,
+            Preset::IndoorNavigation | Preset::Drone => PowerMode::LowNoise,
+        }
+    }
+
+    /// Low-pass filter setting
+    pub fn lowpass_filter(self) -> LowPassFilter {
+        match self {
+            Preset::WeatherMonitoring => LowPassFilter::Disabled,
+            Preset::IndoorNavigation => LowPassFilter::OdrDiv20,
+            Preset::Drone => LowPassFilter::OdrDiv9,
+        }
+    }
+
+    /// Block data update enabled?
+    pub fn bdu(self) -> bool {
+        match self {
+            Preset::WeatherMonitoring => false,
+            Preset::IndoorNavigation | Preset::Drone => true,
+        }
+    }
+
+    /// FIFO mode, `None` with the FIFO disabled
+    pub fn fifo_mode(self) -> Option<FIFO_MODE> {
+        match self {
+            Preset::WeatherMonitoring | Preset::IndoorNavigation => None,
+            Preset::Drone => Some(FIFO_MODE::Stream),
+        }
+    }
+}
+
+impl<T, E> LPS22HB<T>
+where
+    T: Interface<Error = E>,
+{
+    /// Apply a preset. The device is put in power-down mode first and the ODR of the preset is set last.
+    pub fn apply_preset(&mut self, preset: Preset) -> Result<(), T::Error> {
+        self.set_datarate(ODR::PowerDown)?;
+        self.set_power_mode(preset.power_mode())?;
+        self.bdu_enable(preset.bdu())?;
+        match preset.fifo_mode() {
+            Some(fifo_mode) => self.configure_fifo(
+                FIFO_ON::Enabled,
+                FIFOConfig {
+                    fifo_mode,
+                    ..Default::default()
+                },
+            ),
+            None => self.configure_fifo(FIFO_ON::Disabled, FIFOConfig::default()),
+        }?;
+        self.set_lowpass_filter(preset.lowpass_filter(), true)?;
+        self.set_datarate(preset.odr())
+    }
+
+    /// Read the settings back and return the preset they match, if any
+    pub fn get_preset(&mut self) -> Result<Option<Preset>, T::Error> {
+        let odr = self.get_datarate()?;
+        let power_mode = self.get_power_mode()?;
+        let lowpass_filter = self.get_lowpass_filter()?;
+        let bdu = self.is_register_bit_flag_high(Registers::CTRL_REG1, Bitmasks::BDU)?;
+        let fifo_mode = match self.is_register_bit_flag_high(Registers::CTRL_REG2, Bitmasks::FIFO_EN)? {
+            true => FIFO_MODE::try_from(self.read_register(Registers::FIFO_CTRL)?).ok(),
+            false => None,
+        };
+        let preset = [Preset::WeatherMonitoring, Preset::IndoorNavigation, Preset::Drone]
+            .iter()
+            .copied()
+            .find(|preset| {
+                odr == Some(preset.odr())
+                    && power_mode == preset.power_mode()
+                    && lowpass_filter == preset.lowpass_filter()
+                    && bdu == preset.bdu()
+                    && fifo_mode == preset.fifo_mode()
+            });
+        Ok(preset)
+    }
+}
@@ -0,0 +1,110 @@
+//! Station pressure reduction: QFE, QNH and QFF
+//!
+//! - QFE: pressure at the station datum (the sensor pressure corrected for its mounting height)
+//! - QNH: QFE reduced to mean sea level using the standard atmosphere
+//! - QFF: QFE reduced to mean sea level using the actual temperature at the station
+//!
+//! __NOTE__: the temperature channel of the LPS22HB measures the temperature of the die,
+//! which may be higher than the outside air temperature if the sensor is enclosed.
+
+use super::*;
+use altitude::{sea_level_pressure, ZERO_CELSIUS};
+
+/// Standard gravity, m/s²
+const G: f32 = 9.806_65;
+/// Specific gas constant of dry air, J/(kg·K)
+const R_DRY_AIR: f32 = 287.05;
+/// Standard temperature lapse rate, K/m
+const LAPSE_RATE: f32 = 0.0065;
+
+/// Kind of reduction applied to the station pressure
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Reduction {
+    /// Pressure at the station datum
+    Qfe,
+    /// Sea-level pressure, standard atmosphere
+    Qnh,
+    /// Sea-level pressure, actual station temperature
+    Qff,
+}
+
+/// Pressure value tagged with the reduction that produced it
+#[derive(Debug, Clone, Copy)]
+pub struct ReducedPressure {
+    /// Reduction that was applied
+    pub reduction: Reduction,
+    /// Pressure in hPa
+    pub pressure: f32,
+}
+
+/// Station location settings
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Station {
+    /// Elevation of the station datum above mean sea level, in metres
+    pub elevation: f32,
+    /// Height of the sensor above the station datum, in metres
+    pub sensor_height: f32,
+}
+
+impl Station {
+    /// Station with the sensor at the given elevation (metres above mean sea level)
+    pub fn new(elevation: f32) -> Self {
+        Station {
+            elevation,
+            sensor_height: 0.0,
+        }
+    }
+
+    /// QFE in hPa from the pressure measured by the sensor
+    pub fn qfe(&self, pressure: f32) -> f32 {
+        sea_level_pressure(pressure, self.sensor_height)
+    }
+
+    /// QNH in hPa from the pressure measured by the sensor
+    pub fn qnh(&self, pressure: f32) -> f32 {
+        sea_level_pressure(self.qfe(pressure), self.elevation)
+    }
+
+    /// QFF in hPa from the pressure measured by the sensor and the station temperature (°C).
+    /// The mean temperature of the fictitious air column is derived with the standard lapse rate.
+    pub fn qff(&self, pressure: f32, temperature: f32) -> f32 {
+        let mean_temperature = temperature + LAPSE_RATE * self.elevation / 2.0;
+        self.qff_with_mean_temperature(pressure, mean_temperature)
+    }
+
+    /// QFF in hPa from the pressure measured by the sensor and a user-supplied
+    /// mean temperature (°C) of the air column between the station and sea level
+    pub fn qff_with_mean_temperature(&self, pressure: f32, mean_temperature: f32) -> f32 {
+        let kelvin = mean_temperature + ZERO_CELSIUS;
+        self.qfe(pressure) * libm::expf(G * self.elevation / (R_DRY_AIR * kelvin))
+    }
+
+    /// Apply the given reduction; `temperature` (°C) is only used for QFF
+    pub fn reduce(&self, reduction: Reduction, pressure: f32, temperature: f32) -> ReducedPressure {
+        let pressure = match reduction {
+            Reduction::Qfe => self.qfe(pressure),
+            Reduction::Qnh => self.qnh(pressure),
+            Reduction::Qff => self.qff(pressure, temperature),
+        };
+        ReducedPressure {
+            reduction,
+            pressure,
+        }
+    }
+}
+
+impl<T, E, MODE> LPS22HB<T, MODE>
+where
+    T: Interface<Error = E>,
+{
+    /// Read the pressure and reduce it for the given station.
+    /// QFF uses the temperature channel of the sensor.
+    pub fn read_reduced_pressure(
+        &mut self,
+        station: &Station,
+        reduction: Reduction,
+    ) -> Result<ReducedPressure, T::Error> {
+        let measurement = self.read_measurement()?;
+        Ok(station.reduce(reduction, measurement.pressure, measurement.temperature))
+    }
+}
@@ -3,7 +3,7 @@
 
 /// LPS22HB Registers
 #[allow(non_camel_case_types)]
-#[derive(Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum Registers {
     /// Interrupt control.
     INTERRUPT_CFG = 0x0B,
@@ -58,6 +58,86 @@ impl Registers {
     pub fn addr(self) -> u8 {
         self as u8
     }
+
+    /// Register at the given address, `None` for reserved addresses
+    pub fn from_addr(addr: u8) -> Option<Registers> {
+        use Registers::*;
+        let register = match addr {
+            0x0B => INTERRUPT_CFG,
+            0x0C => THS_P_L,
+            0x0D => THS_P_H,
+            0x0F => WHO_AM_I,
+            0x10 => CTRL_REG1,
+            0x11 => CTRL_REG2,
+            0x12 => CTRL_REG3,
+            0x14 => FIFO_CTRL,
+            0x15 => REF_P_XL,
+            0x16 => REF_P_L,
+            0x17 => REF_P_H,
+            0x18 => RPDS_L,
+            0x19 => RPDS_H,
+            0x1A => RES_CONF,
+            0x25 => INT_SOURCE,
+            0x26 => FIFO_STATUS,
+            0x27 => STATUS,
+            0x28 => PRESS_OUT_XL,
+            0x29 => PRESS_OUT_L,
+            0x2A => PRESS_OUT_H,
+            0x2B => TEMP_OUT_L,
+            0x2C => TEMP_OUT_H,
+            0x33 => LPFP_RES,
+            _ => return None,
+        };
+        Some(register)
+    }
+
+    /// Name of the register, as in the datasheet
+    pub fn name(self) -> &'static str {
+        use Registers::*;
+        match self {
+            INTERRUPT_CFG => "INTERRUPT_CFG",
+            THS_P_L => "THS_P_L",
+            THS_P_H => "THS_P_H",
+            WHO_AM_I => "WHO_AM_I",
+            CTRL_REG1 => "CTRL_REG1",
+            CTRL_REG2 => "CTRL_REG2",
+            CTRL_REG3 => "CTRL_REG3",
+            FIFO_CTRL => "FIFO_CTRL",
+            REF_P_XL => "REF_P_XL",
+            REF_P_L => "REF_P_L",
+            REF_P_H => "REF_P_H",
+            RPDS_L => "RPDS_L",
+            RPDS_H => "RPDS_H",
+            RES_CONF => "RES_CONF",
+            INT_SOURCE => "INT_SOURCE",
+            FIFO_STATUS => "FIFO_STATUS",
+            STATUS => "STATUS",
+            PRESS_OUT_XL => "PRESS_OUT_XL",
+            PRESS_OUT_L => "PRESS_OUT_L",
+            PRESS_OUT_H => "PRESS_OUT_H",
+            TEMP_OUT_L => "TEMP_OUT_L",
+            TEMP_OUT_H => "TEMP_OUT_H",
+            LPFP_RES => "LPFP_RES",
+        }
+    }
+
+    /// Can the register be written?
+    pub fn is_writable(self) -> bool {
+        use Registers::*;
+        !matches!(
+            self,
+            WHO_AM_I
+                | INT_SOURCE
+                | FIFO_STATUS
+                | STATUS
+                | PRESS_OUT_XL
+                | PRESS_OUT_L
+                | PRESS_OUT_H
+                | TEMP_OUT_L
+                | TEMP_OUT_H
+                | LPFP_RES
+        )
+    }
 }
 
 /// LPS22HB Bit masks
@@ -1,9 +1,45 @@
 //! Functions related to sensor measurements: reading value or status, setting offset and reference
-//!
-//! TO DO: add reference pressure setting
 
 use super::*;
 
+/// Pressure and temperature sample read in one go
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Measurement {
+    /// Pressure in hPa
+    pub pressure: f32,
+    /// Temperature in degrees Celsius
+    pub temperature: f32,
+}
+
+/// Raw pressure and temperature sample (fixed-point, as stored in the output registers)
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RawMeasurement {
+    /// Pressure in 1/4096 hPa
+    pub pressure: i32,
+    /// Temperature in 1/100 degrees Celsius
+    pub temperature: i16,
+}
+
+impl RawMeasurement {
+    /// Decode the PRESS_OUT_XL..TEMP_OUT_H output registers (5 bytes)
+    pub(crate) fn from_bytes(data: &[u8; 5]) -> Self {
+        RawMeasurement {
+            // 24-bit two's complement, negative pressure differences are possible with AUTOZERO
+            pressure: (data[2] as i8 as i32) << 16 | (data[1] as i32) << 8 | (data[0] as i32),
+            temperature: (data[4] as i16) << 8 | (data[3] as i16),
+        }
+    }
+}
+
+impl From<RawMeasurement> for Measurement {
+    fn from(raw: RawMeasurement) -> Self {
+        Measurement {
+            pressure: (raw.pressure as f32) / PRESS_SCALE,
+            temperature: (raw.temperature as f32) / TEMP_SCALE,
+        }
+    }
+}
+
 #[derive(Debug)]
 /// Contents of the STATUS register (pressure and temperature overrun and data availability flags)
 pub struct DataStatus {
@@ -13,7 +49,7 @@ pub struct DataStatus {
     pub press_available: bool,
 }
 
-impl<T, E> LPS22HB<T>
+impl<T, E, MODE> LPS22HB<T, MODE>
 where
     T: Interface<Error = E>,
 {
@@ -28,32 +64,41 @@ where
      /// Calculated pressure reading in hPa
      pub fn read_pressure(&mut self) -> Result<f32, T::Error> {
         let mut data = [0u8; 3];
-        self.interface.read(
-            Registers::PRESS_OUT_XL.addr(),
-            &mut data,
-        )?;
-        let p: i32 = (data[2] as i32) << 16 | (data[1] as i32) << 8 | (data[0] as i32);
-        let pressure = (p as f32) / PRESS_SCALE; // no need to take care of negative values
+        self.read_block(Registers::PRESS_OUT_XL, &mut data)?;
+        let p: i32 = (data[2] as i8 as i32) << 16 | (data[1] as i32) << 8 | (data[0] as i32);
+        let pressure = (p as f32) / PRESS_SCALE; // negative with AUTOZERO enabled
         Ok(pressure)
     }
 
     /// Calculated temperaure reading in degrees Celsius
     pub fn read_temperature(&mut self) -> Result<f32, T::Error> {
         let mut data = [0u8; 2];
-        self.interface.read(
-            Registers::TEMP_OUT_L.addr(),
-            &mut data,
-        )?;
+        self.read_block(Registers::TEMP_OUT_L, &mut data)?;
         let t: i16 = (data[1] as i16) << 8 | (data[0] as i16);
         let temperature = (t as f32) / TEMP_SCALE;
         Ok(temperature)
     }
   
+    /// Pressure (hPa) and temperature (degrees Celsius) read in a single transaction.
+    /// In FIFO mode each call pops one sample from the FIFO.
+    pub fn read_measurement(&mut self) -> Result<Measurement, T::Error> {
+        let mut data = [0u8; 5];
+        self.read_block(Registers::PRESS_OUT_XL, &mut data)?;
+        Ok(RawMeasurement::from_bytes(&data).into())
+    }
+
+    /// Raw pressure and temperature read in a single transaction, without scaling
+    pub fn read_measurement_raw(&mut self) -> Result<RawMeasurement, T::Error> {
+        let mut data = [0u8; 5];
+        self.read_block(Registers::PRESS_OUT_XL, &mut data)?;
+        Ok(RawMeasurement::from_bytes(&data))
+    }
+
     /// Calculated reference pressure reading in hPa
     pub fn read_reference_pressure(&mut self) -> Result<f32, T::Error> {
         let mut data = [0u8; 3];
-        self.interface.read(Registers::REF_P_XL.addr(), &mut data)?;
-        let p: i32 = (data[2] as i32) << 16 | (data[1] as i32) << 8 | (data[0] as i32);
+        self.read_block(Registers::REF_P_XL, &mut data)?;
+        let p: i32 = (data[2] as i8 as i32) << 16 | (data[1] as i32) << 8 | (data[0] as i32);
         let pressure: f32 = (p as f32) / PRESS_SCALE;
         Ok(pressure)
     }
@@ -61,7 +106,7 @@ where
     /// Read pressure offset value, 16-bit data that can be used to implement One-Point Calibration (OPC) after soldering.
     pub fn read_pressure_offset(&mut self) -> Result<i16, T::Error> {
         let mut data = [0u8; 2];
-        self.interface.read(Registers::RPDS_L.addr(), &mut data)?;
+        self.read_block(Registers::RPDS_L, &mut data)?;
         let o: i16 = (data[1] as i16) << 8 | (data[0] as i16);
         Ok(o)
     }
@@ -69,117 +114,43 @@ where
     /// Read threshold value for pressure interrupt generation
     pub fn read_threshold(&mut self) -> Result<i16, T::Error> {
         let mut data = [0u8; 2];
-        self.interface.read(Registers::THS_P_L.addr(), &mut data)?;
+        self.read_block(Registers::THS_P_L, &mut data)?;
         let o: i16 = (data[1] as i16) << 8 | (data[0] as i16);
         Ok(o)
     }
  
-    /// Set the pressure offset value (VALUE IN hPA!)
+    /// Set the pressure threshold value for interrupt generation (VALUE IN hPA!).
+    /// Values above 4095 hPa saturate at the register maximum (65535/16 hPa).
     pub fn set_threshold(&mut self, threshold: u16) -> Result<(), T::Error> {
-        let mut payload = [0u8; 2];
-        let threshold = threshold * 16;
-
-        payload[0] = (threshold & 0xff) as u8; // lower byte
-        payload[1] = (threshold >> 8) as u8; // upper byte
-
-        self.interface.write(Registers::THS_P_L.addr(), payload[0])?;
-        self.interface.write(Registers::THS_P_H.addr(), payload[1])?;
+        self.set_threshold_raw(threshold.saturating_mul(16))
+    }
 
-        Ok(())
+    /// Set the pressure threshold value for interrupt generation in register units (1/16 hPa)
+    pub fn set_threshold_raw(&mut self, threshold: u16) -> Result<(), T::Error> {
+        // lower and upper byte in one transaction
+        self.write_registers(Registers::THS_P_L, &threshold.to_le_bytes())
     }
 
-    /// Set the pressure offset value (VALUE IN hPA!)
+    /// Set the pressure offset value (VALUE IN hPA!).
+    /// Values above 2047 hPa saturate at the register maximum (32767/16 hPa).
     pub fn set_pressure_offset(&mut self, offset: u16) -> Result<(), T::Error> {
-        let mut payload = [0u8; 2];
-        let offset = offset * 16;
-
-        payload[0] = (offset & 0xff) as u8; // lower byte
-        payload[1] = (offset >> 8) as u8; // upper byte
-
-        self.interface.write(Registers::RPDS_L.addr(), payload[0])?;
-        self.interface.write(Registers::RPDS_H.addr(), payload[1])?;
+        let offset = (offset as i32 * 16).min(i16::MAX as i32) as i16;
+        self.set_pressure_offset_raw(offset)
+    }
 
-        Ok(())
+    /// Set the pressure offset value in register units (1/16 hPa, two's complement).
+    /// The offset is subtracted from the measured pressure.
+    pub fn set_pressure_offset_raw(&mut self, offset: i16) -> Result<(), T::Error> {
+        self.write_registers(Registers::RPDS_L, &offset.to_le_bytes())
     }
 
-    /// Set the reference pressure (value in hPA)
+    /// Set the reference pressure (value in hPA), used when the AUTOZERO or AUTORIFP function is enabled.
+    /// Note that enabling AUTOZERO or AUTORIFP overwrites it with the next measured pressure.
+    /// Values above 2047 hPa saturate at the register maximum (0x7FFFFF/4096 hPa).
     pub fn set_reference_pressure(&mut self, pressure: u16) -> Result<(), T::Error> {
-        
-        /*
-        self.interface.read(Registers::REF_P_XL.addr(), &mut data)?;
-        let p: i32 = (data[2] as i32) << 16 | (data[1] as i32) << 8 | (data[0] as i32);
-        let pressure: f32 = (p as f32) / PRESS_SCALE;
-        
-        
-        let pressure = pressure * PRESS_SCALE;
-
-        let mut payload = [0u8; 3];
-        
-        // value must be split into three bytes
-
-        payload[0] = (pressure & 0xff) as u8; // XL byte
-        payload[1] = (pressure >> 8) as u8; // L byte
-        payload[2] = (pressure >> 16) as u8; // H byte
-                
-        */
-        
-
-        /*
-
-                /**
-                 * @brief  pressure_ref:   The Reference pressure value is a 24-bit data
-                *         expressed as 2’s complement. The value is used when AUTOZERO
-                *         or AUTORIFP function is enabled.[set]
-                *
-                * @param  ctx    Read / write interface definitions
-                * @param  buff   Buffer that contains data to write
-                * @retval        Interface status (MANDATORY: return 0 -> no Error).
-                *
-                */
-                int32_t lps22hb_pressure_ref_set(stmdev_ctx_t *ctx, int32_t val)
-                {
-                uint8_t buff[3];
-                int32_t ret;
-
-                buff[2] = (uint8_t)((uint32_t)val / 65536U);
-                buff[1] = (uint8_t)((uint32_t)val - (buff[2] * 65536U)) / 256U;
-                buff[0] = (uint8_t)((uint32_t)val - (buff[2] * 65536U) -
-                                    (buff[1] * 256U));
-                ret =  lps22hb_write_reg(ctx, LPS22HB_REF_P_XL, buff, 3);
-
-                return ret;
-                }
-
-                /**
-                 * @brief  pressure_ref:   The Reference pressure value is a 24-bit data
-                *         expressed as 2’s complement. The value is used when AUTOZERO
-                *         or AUTORIFP function is enabled.[get]
-                *
-                * @param  ctx    Read / write interface definitions
-                * @param  buff   Buffer that stores data read
-                * @retval        Interface status (MANDATORY: return 0 -> no Error).
-                *
-                */
-                int32_t lps22hb_pressure_ref_get(stmdev_ctx_t *ctx, int32_t *val)
-                {
-                uint8_t buff[3];
-                int32_t ret;
-
-                ret =  lps22hb_read_reg(ctx, LPS22HB_REF_P_XL, buff, 3);
-                *val = (int32_t)buff[2];
-                *val = (*val * 256) + (int32_t)buff[1];
-                *val = (*val * 256) + (int32_t)buff[0];
-
-                return ret;
-                }
-
-        */
-
-
-
-
-        
-        Ok(())
+        let pressure = (pressure as i32 * PRESS_SCALE as i32).min(0x7F_FFFF);
+        // 24-bit two's complement, XL, L and H bytes in one transaction
+        self.write_registers(Registers::REF_P_XL, &pressure.to_le_bytes()[..3])
     }
 
     /// Get all the flags from the STATUS_REG register
@@ -189,40 +160,38 @@ where
         let reg_value = self.read_register(Registers::STATUS)?;
 
         let status = DataStatus {
-            /// Has new pressure data overwritten the previous one?
-            press_overrun: match reg_value & Bitmasks::P_OR {
-                0 => false,
-                _ => true,
-            },
-            /// Has new temperature data overwritten the previous one?
-            temp_overrun: match reg_value & Bitmasks::T_OR {
-                0 => false,
-                _ => true,
-            },
-            /// Is new pressure data available?
-            press_available: match reg_value & Bitmasks::P_DA {
-                0 => false,
-                _ => true,
-            },
-            /// Is new temperature data available?
-            temp_available: match reg_value & Bitmasks::T_DA {
-                0 => false,
-                _ => true,
-            },
+            // Has new pressure data overwritten the previous one?
+            press_overrun: reg_value & Bitmasks::P_OR != 0,
+            // Has new temperature data overwritten the previous one?
+            temp_overrun: reg_value & Bitmasks::T_OR != 0,
+            // Is new pressure data available?
+            press_available: reg_value & Bitmasks::P_DA != 0,
+            // Is new temperature data available?
+            temp_available: reg_value & Bitmasks::T_DA != 0,
         };
 
         Ok(status)
     }
 
+    pub(crate) fn write_one_shot(&mut self) -> Result<(), T::Error> {
+        // reading the output registers clears P_DA and T_DA, which may still be set
+        // by an unread sample, so that they only report this acquisition
+        self.read_measurement_raw()?;
+        self.set_register_bit_flag(Registers::CTRL_REG2, Bitmasks::ONE_SHOT)
+    }
+}
+
+impl<T, E> LPS22HB<T>
+where
+    T: Interface<Error = E>,
+{
     /// Triggers the one-shot mode, and a new acquisition starts when it is required.
     /// Enabling this mode is possible only if the device was previously in power-down mode.
     /// Once the acquisition is completed and the output registers updated,
     /// the device automatically enters in power-down mode. ONE_SHOT bit self-clears itself.
+    /// An unread sample is discarded first, so that the data available flags report this acquisition.
     pub fn one_shot(&mut self) -> Result<(), T::Error> {
         self.set_datarate(ODR::PowerDown)?; // make sure that Power down/one shot mode is enabled
-        self.set_register_bit_flag(Registers::CTRL_REG2, Bitmasks::ONE_SHOT)?;
-        Ok(())
+        self.write_one_shot()
     }
-
-
 }
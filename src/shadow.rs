@@ -0,0 +1,139 @@
+//! Optional shadow copy of the configuration registers
+//!
+//! With the shadow enabled, the driver keeps a copy of the writable
+//! configuration registers and uses it instead of reading them back from the
+//! device, so most configuration changes become a single write.
+//! The copy is filled on first use or by `sync()`, and dropped after a
+//! software reset or a reboot.
+//!
+//! The reference pressure (REF_P) is never cached, as AUTOZERO and AUTORIFP
+//! change it in the device.
+
+use super::*;
+
+/// First cached address (INTERRUPT_CFG)
+const FIRST: u8 = 0x0B;
+/// Size of the address range covering the cached registers (up to RES_CONF)
+const SIZE: usize = 0x10;
+
+/// Registers kept in the shadow copy
+const CACHED: [Registers; 10] = [
+    Registers::INTERRUPT_CFG,
+    Registers::THS_P_L,
+    Registers::THS_P_H,
+    Registers::CTRL_REG1,
+    Registers::CTRL_REG2,
+    Registers::CTRL_REG3,
+    Registers::FIFO_CTRL,
+    Registers::RPDS_L,
+    Registers::RPDS_H,
+    Registers::RES_CONF,
+];
+
+/// Contiguous blocks of cached registers (first register, length), read in one transaction each
+const BLOCKS: [(Registers, usize); 4] = [
+    (Registers::INTERRUPT_CFG, 3),
+    (Registers::CTRL_REG1, 3),
+    (Registers::FIFO_CTRL, 1),
+    (Registers::RPDS_L, 3),
+];
+
+/// Shadow copy of the cached registers
+#[derive(Debug, Clone)]
+pub(crate) struct Shadow {
+    enabled: bool,
+    values: [Option<u8>; SIZE],
+    /// State of the address auto-increment (IF_ADD_INC), tracked even with the copy disabled
+    auto_increment: bool,
+}
+
+impl Shadow {
+    pub(crate) fn new() -> Self {
+        Shadow {
+            enabled: false,
+            values: [None; SIZE],
+            auto_increment: true,
+        }
+    }
+
+    fn index(register: Registers) -> Option<usize> {
+        match CACHED.contains(&register) {
+            true => Some((register.addr() - FIRST) as usize),
+            false => None,
+        }
+    }
+
+    /// Content of the register after `value` was written, without the self-clearing bits
+    fn settled(register: Registers, value: u8) -> u8 {
+        match register {
+            Registers::CTRL_REG2 => value & !(Bitmasks::BOOT | Bitmasks::SWRESET | Bitmasks::ONE_SHOT),
+            Registers::INTERRUPT_CFG => {
+                // resetting AUTOZERO or AUTORIFP also clears its enable bit
+                let mut cleared = Bitmasks::RESET_ARP | Bitmasks::RESET_AZ;
+                if value & Bitmasks::RESET_AZ != 0 {
+                    cleared |= Bitmasks::AUTOZERO;
+                }
+                if value & Bitmasks::RESET_ARP != 0 {
+                    cleared |= Bitmasks::AUTORIFP;
+                }
+                value & !cleared
+            }
+            _ => value,
+        }
+    }
+
+    /// Cached value of the register, if any
+    pub(crate) fn get(&self, register: Registers) -> Option<u8> {
+        match self.enabled {
+            true => Self::index(register).and_then(|i| self.values[i]),
+            false => None,
+        }
+    }
+
+    /// Is the register address incremented during multiple byte accesses?
+    pub(crate) fn auto_increment(&self) -> bool {
+        self.auto_increment
+    }
+
+    /// Update the copy after the register was read or written
+    pub(crate) fn store(&mut self, register: Registers, value: u8) {
+        if register == Registers::CTRL_REG2 {
+            self.auto_increment = value & Bitmasks::IF_ADD_INC != 0;
+        }
+        if let (true, Some(i)) = (self.enabled, Self::index(register)) {
+            self.values[i] = Some(Self::settled(register, value));
+        }
+    }
+
+    pub(crate) fn invalidate(&mut self) {
+        self.values = [None; SIZE];
+    }
+}
+
+impl<T, E, MODE> LPS22HB<T, MODE>
+where
+    T: Interface<Error = E>,
+{
+    /// Enable or disable the shadow copy of the configuration registers (default disabled).
+    /// The copy starts empty, use `sync()` to fill it in one go.
+    pub fn shadow_enable(&mut self, flag: bool) {
+        self.shadow.enabled = flag;
+        self.shadow.invalidate();
+    }
+
+    /// Re-read the cached configuration registers from the device into the shadow copy
+    pub fn sync(&mut self) -> Result<(), T::Error> {
+        self.shadow.invalidate();
+        for (first, len) in BLOCKS.iter() {
+            let mut values = [0u8; 3];
+            self.read_registers(*first, &mut values[..*len])?;
+        }
+        Ok(())
+    }
+
+    /// Forget the shadow copy, e.g. after the device lost power.
+    /// Done automatically by `software_reset()` and `reboot()`.
+    pub fn invalidate_shadow(&mut self) {
+        self.shadow.invalidate();
+    }
+}
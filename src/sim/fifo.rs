@@ -0,0 +1,101 @@
+//! Simulated 32-slot FIFO
+
+use crate::fifo::FIFO_DEPTH;
+
+/// One FIFO slot: PRESS_OUT_XL..PRESS_OUT_H and TEMP_OUT_L..TEMP_OUT_H
+pub(super) type Slot = [u8; 5];
+
+/// Behaviour of the FIFO for the current mode and trigger state
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(super) enum Behaviour {
+    /// FIFO not used, data goes to the output registers
+    Bypass,
+    /// Collect data until full, then stop
+    Fifo,
+    /// Collect data, discarding the oldest sample when full
+    Stream,
+    /// Stream mode with the depth limited to the watermark level
+    DynamicStream,
+}
+
+impl Behaviour {
+    /// Behaviour for the FIFO_MODE bits (FIFO_CTRL[7:5]) and the trigger state.
+    /// The trigger event of the trigger-based modes is an interrupt event (INT_SOURCE IA).
+    pub(super) fn from_mode(mode: u8, triggered: bool) -> Self {
+        match (mode, triggered) {
+            (0b001, _) => Behaviour::Fifo,
+            (0b010, _) => Behaviour::Stream,
+            (0b110, _) => Behaviour::DynamicStream,
+            (0b011, false) => Behaviour::Stream,
+            (0b011, true) => Behaviour::Fifo,
+            (0b100, true) => Behaviour::Stream,
+            (0b111, true) => Behaviour::Fifo,
+            _ => Behaviour::Bypass,
+        }
+    }
+}
+
+/// FIFO storage
+#[derive(Debug, Clone)]
+pub(super) struct Fifo {
+    slots: [Slot; FIFO_DEPTH],
+    head: usize,
+    len: usize,
+    /// At least one sample was overwritten since the last read
+    pub(super) overrun: bool,
+    /// Trigger event seen, for the trigger-based modes
+    pub(super) triggered: bool,
+}
+
+impl Fifo {
+    pub(super) fn new() -> Self {
+        Fifo {
+            slots: [[0; 5]; FIFO_DEPTH],
+            head: 0,
+            len: 0,
+            overrun: false,
+            triggered: false,
+        }
+    }
+
+    pub(super) fn len(&self) -> usize {
+        self.len
+    }
+
+    pub(super) fn clear(&mut self) {
+        self.head = 0;
+        self.len = 0;
+        self.overrun = false;
+    }
+
+    /// Oldest sample
+    pub(super) fn front(&self) -> Option<Slot> {
+        match self.len {
+            0 => None,
+            _ => Some(self.slots[self.head]),
+        }
+    }
+
+    /// Store a sample in a FIFO limited to `depth` slots
+    pub(super) fn push(&mut self, slot: Slot, behaviour: Behaviour, depth: usize) {
+        if self.len >= depth {
+            match behaviour {
+                Behaviour::Stream | Behaviour::DynamicStream => {
+                    self.pop();
+                    self.overrun = true;
+                }
+                _ => return,
+            }
+        }
+        self.slots[(self.head + self.len) % FIFO_DEPTH] = slot;
+        self.len += 1;
+    }
+
+    /// Remove the oldest sample
+    pub(super) fn pop(&mut self) {
+        if self.len > 0 {
+            self.head = (self.head + 1) % FIFO_DEPTH;
+            self.len -= 1;
+        }
+    }
+}
@@ -0,0 +1,468 @@
+//! Register-level simulator of the LPS22HB (requires the `sim` feature)
+//!
+//! [`SimulatedLps22hb`] implements [`Interface`], so the driver can run on the
+//! host without hardware. It models:
+//! - the register map with its power-on defaults, and read-only registers
+//! - the self-clearing ONE_SHOT, SWRESET, BOOT, RESET_AZ and RESET_ARP bits
+//! - clear-on-read of INT_SOURCE and of the STATUS data available flags
+//! - address auto-increment during multiple byte reads and writes (IF_ADD_INC)
+//! - the pressure offset (RPDS), AUTOZERO, AUTORIFP and threshold interrupts
+//! - injectable pressure and temperature waveforms, see [`Waveform`]
+//! - continuous conversions at the configured ODR as virtual time advances
+//! - the 32-slot FIFO in all FIFO_MODEs, with the FTH, OVR and FSS status; in Dynamic-Stream
+//!   mode the depth is limited to the watermark level
+//! - the INT_DRDY pin as configured in CTRL_REG3, see [`SimulatedLps22hb::int_drdy`]
+//!
+//! A conversion happens on every one-shot, at every ODR period elapsed in
+//! [`SimulatedLps22hb::advance`], or with [`SimulatedLps22hb::trigger_conversion`].
+//!
+//! ```
+//! use lps22hb::sim::{SimulatedLps22hb, Waveform};
+//! use lps22hb::LPS22HB;
+//!
+//! let sim = SimulatedLps22hb::with_waveforms(Waveform::Constant(1013.25), Waveform::Constant(21.5));
+//! let mut lps22 = LPS22HB::new(sim);
+//! lps22.one_shot().unwrap();
+//! assert_eq!(lps22.read_pressure().unwrap(), 1013.25);
+//! assert_eq!(lps22.read_temperature().unwrap(), 21.5);
+//! ```
+
+mod fifo;
+mod waveform;
+pub use self::waveform::Waveform;
+
+use self::fifo::{Behaviour, Fifo, Slot};
+
+use crate::fifo::FIFO_DEPTH;
+use crate::interface::Interface;
+use crate::register::{Bitmasks, Registers};
+use crate::{OFFSET_SCALE, PRESS_SCALE, TEMP_SCALE};
+
+/// Size of the simulated address space (up to LPFP_RES)
+const REGISTER_SPACE: usize = 0x34;
+
+/// Content of the WHO_AM_I register
+const WHO_AM_I_VALUE: u8 = 0b1011_0001;
+
+/// Errors reported by the simulator
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SimError {
+    /// Access to a reserved address
+    InvalidAddress(u8),
+    /// Write to a read-only register
+    ReadOnly(Registers),
+}
+
+/// Simulated LPS22HB
+#[derive(Debug, Clone)]
+pub struct SimulatedLps22hb {
+    registers: [u8; REGISTER_SPACE],
+    pressure: Waveform,
+    temperature: Waveform,
+    conversions: u32,
+    capture_reference: bool,
+    fifo: Fifo,
+    /// Virtual time since power-on (µs)
+    time: u64,
+    /// Virtual time since the ODR was last changed (µs)
+    odr_time: u64,
+    /// Conversions at the current ODR
+    odr_conversions: u64,
+}
+
+impl Default for SimulatedLps22hb {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SimulatedLps22hb {
+    /// Create a simulator at power-on, measuring 1013.25 hPa and 25 °C
+    pub fn new() -> Self {
+        Self::with_waveforms(Waveform::Constant(1013.25), Waveform::Constant(25.0))
+    }
+
+    /// Create a simulator at power-on with the given pressure (hPa) and temperature (°C) signals
+    pub fn with_waveforms(pressure: Waveform, temperature: Waveform) -> Self {
+        let mut sim = SimulatedLps22hb {
+            registers: [0; REGISTER_SPACE],
+            pressure,
+            temperature,
+            conversions: 0,
+            capture_reference: false,
+            fifo: Fifo::new(),
+            time: 0,
+            odr_time: 0,
+            odr_conversions: 0,
+        };
+        sim.power_on_reset();
+        sim
+    }
+
+    /// Change the pressure signal (hPa)
+    pub fn set_pressure(&mut self, pressure: Waveform) {
+        self.pressure = pressure;
+    }
+
+    /// Change the temperature signal (°C)
+    pub fn set_temperature(&mut self, temperature: Waveform) {
+        self.temperature = temperature;
+    }
+
+    /// Number of conversions performed so far
+    pub fn conversions(&self) -> u32 {
+        self.conversions
+    }
+
+    /// Content of a register, without the side effects of a bus read
+    pub fn register(&self, register: Registers) -> u8 {
+        self.registers[register.addr() as usize]
+    }
+
+    /// Overwrite a register, bypassing the write rules (e.g. to inject a status)
+    pub fn set_register(&mut self, register: Registers, value: u8) {
+        self.registers[register.addr() as usize] = value;
+    }
+
+    /// Virtual time elapsed since power-on, in µs
+    pub fn time(&self) -> u64 {
+        self.time
+    }
+
+    /// Advance virtual time by `us` microseconds, converting at the ODR set in CTRL_REG1.
+    /// The first sample is available one period after the ODR was set.
+    pub fn advance(&mut self, us: u32) {
+        self.time += us as u64;
+        let rate = match Self::odr_hz(self.register(Registers::CTRL_REG1)) {
+            Some(rate) => rate,
+            None => return,
+        };
+        self.odr_time += us as u64;
+        while (self.odr_conversions + 1) * 1_000_000 <= self.odr_time * rate {
+            self.odr_conversions += 1;
+            self.trigger_conversion();
+        }
+    }
+
+    /// Level of the INT_DRDY pin, as configured in CTRL_REG3 (`true` is high)
+    pub fn int_drdy(&self) -> bool {
+        let ctrl_reg3 = self.register(Registers::CTRL_REG3);
+        let int_source = self.register(Registers::INT_SOURCE);
+        let fifo_status = self.register(Registers::FIFO_STATUS);
+        let high = int_source & Bitmasks::PH != 0;
+        let low = int_source & Bitmasks::PL != 0;
+        let active = match ctrl_reg3 & Bitmasks::INT_S_MASK {
+            0b00 => {
+                let flag = |enable: u8, set: bool| ctrl_reg3 & enable != 0 && set;
+                flag(Bitmasks::DRDY, self.register(Registers::STATUS) & Bitmasks::P_DA != 0)
+                    || flag(Bitmasks::F_FSS5, self.fifo.len() >= FIFO_DEPTH)
+                    || flag(Bitmasks::F_FTH, fifo_status & Bitmasks::FTH_FIFO != 0)
+                    || flag(Bitmasks::F_OVR, fifo_status & Bitmasks::OVR != 0)
+            }
+            0b01 => high,
+            0b10 => low,
+            _ => high || low,
+        };
+        // INT_H_L selects active low
+        active != (ctrl_reg3 & Bitmasks::INT_H_L != 0)
+    }
+
+    /// Acquire a new pressure and temperature sample, as at the end of a conversion
+    pub fn trigger_conversion(&mut self) {
+        let n = self.conversions;
+        self.conversions = n.wrapping_add(1);
+
+        let rpds = i16::from_le_bytes([
+            self.register(Registers::RPDS_L),
+            self.register(Registers::RPDS_H),
+        ]);
+        let pressure = libm::roundf(self.pressure.sample(n) * PRESS_SCALE) as i32
+            - (rpds as i32) * (PRESS_SCALE / OFFSET_SCALE) as i32;
+        let temperature = libm::roundf(self.temperature.sample(n) * TEMP_SCALE) as i16;
+
+        let interrupt_cfg = self.register(Registers::INTERRUPT_CFG);
+        if self.capture_reference {
+            self.write_24bit(Registers::REF_P_XL, pressure);
+            self.capture_reference = false;
+        }
+        let reference = match interrupt_cfg & (Bitmasks::AUTOZERO | Bitmasks::AUTORIFP) {
+            0 => 0,
+            _ => self.read_24bit(Registers::REF_P_XL),
+        };
+        let output = match interrupt_cfg & Bitmasks::AUTOZERO {
+            0 => pressure,
+            _ => pressure - reference,
+        };
+
+        let mut status = self.register(Registers::STATUS);
+        if status & Bitmasks::P_DA != 0 {
+            status |= Bitmasks::P_OR;
+        }
+        if status & Bitmasks::T_DA != 0 {
+            status |= Bitmasks::T_OR;
+        }
+        self.set_register(Registers::STATUS, status | Bitmasks::P_DA | Bitmasks::T_DA);
+
+        // the sample of the trigger event is stored after a switch from Bypass,
+        // and before a switch from Stream to FIFO mode
+        let before = self.fifo_behaviour();
+        self.update_interrupt(pressure - reference);
+        let behaviour = match before {
+            Behaviour::Stream | Behaviour::DynamicStream => before,
+            _ => self.fifo_behaviour(),
+        };
+
+        let mut slot = [0; 5];
+        slot[..3].copy_from_slice(&output.to_le_bytes()[..3]);
+        slot[3..].copy_from_slice(&temperature.to_le_bytes());
+        self.store_sample(slot, behaviour);
+    }
+
+    /// Behaviour of the FIFO for the current configuration
+    fn fifo_behaviour(&self) -> Behaviour {
+        if self.register(Registers::CTRL_REG2) & Bitmasks::FIFO_EN == 0 {
+            return Behaviour::Bypass;
+        }
+        let mode = self.register(Registers::FIFO_CTRL) >> 5;
+        Behaviour::from_mode(mode, self.fifo.triggered)
+    }
+
+    /// Store a converted sample in the FIFO or in the output registers
+    fn store_sample(&mut self, slot: Slot, behaviour: Behaviour) {
+        match behaviour {
+            Behaviour::Bypass => {
+                self.fifo.clear();
+                self.write_output(slot);
+            }
+            behaviour => {
+                // Dynamic-Stream keeps at most the watermark level of samples (all 32 for a level of 0)
+                let watermark = (self.register(Registers::FIFO_CTRL) & Bitmasks::WTM_MASK) as usize;
+                let depth = match behaviour {
+                    Behaviour::DynamicStream if watermark > 0 => watermark,
+                    Behaviour::DynamicStream => FIFO_DEPTH,
+                    _ if self.register(Registers::CTRL_REG2) & Bitmasks::STOP_ON_FTH != 0 => {
+                        watermark.max(1)
+                    }
+                    _ => FIFO_DEPTH,
+                };
+                self.fifo.push(slot, behaviour, depth);
+                if let Some(front) = self.fifo.front() {
+                    self.write_output(front);
+                }
+            }
+        }
+        self.update_fifo_status();
+    }
+
+    fn write_output(&mut self, slot: Slot) {
+        let addr = Registers::PRESS_OUT_XL.addr() as usize;
+        self.registers[addr..addr + 5].copy_from_slice(&slot);
+    }
+
+    /// Update FIFO_STATUS from the FIFO level and flags
+    fn update_fifo_status(&mut self) {
+        let level = self.fifo.len();
+        let watermark = (self.register(Registers::FIFO_CTRL) & Bitmasks::WTM_MASK) as usize;
+        let mut status = level as u8 & Bitmasks::FSS_MASK;
+        if level > 0 && level >= watermark {
+            status |= Bitmasks::FTH_FIFO;
+        }
+        if self.fifo.overrun {
+            status |= Bitmasks::OVR;
+        }
+        self.set_register(Registers::FIFO_STATUS, status);
+    }
+
+    /// Output data rate in Hz for the ODR bits of CTRL_REG1
+    fn odr_hz(ctrl_reg1: u8) -> Option<u64> {
+        match (ctrl_reg1 & Bitmasks::ODR_MASK) >> 4 {
+            0b001 => Some(1),
+            0b010 => Some(10),
+            0b011 => Some(25),
+            0b100 => Some(50),
+            0b101 => Some(75),
+            _ => None,
+        }
+    }
+
+    /// Update INT_SOURCE after a conversion, `difference` in LSB
+    fn update_interrupt(&mut self, difference: i32) {
+        let interrupt_cfg = self.register(Registers::INTERRUPT_CFG);
+        if interrupt_cfg & Bitmasks::DIFF_EN == 0 {
+            return;
+        }
+        let threshold = u16::from_le_bytes([
+            self.register(Registers::THS_P_L),
+            self.register(Registers::THS_P_H),
+        ]) as i32
+            * (PRESS_SCALE / OFFSET_SCALE) as i32;
+        let mut source = 0;
+        if interrupt_cfg & Bitmasks::PHE != 0 && difference > threshold {
+            source |= Bitmasks::PH;
+        }
+        if interrupt_cfg & Bitmasks::PLE != 0 && difference < -threshold {
+            source |= Bitmasks::PL;
+        }
+        if source != 0 {
+            source |= Bitmasks::IA;
+            // trigger event of the Stream-to-FIFO, Bypass-to-Stream and Bypass-to-FIFO modes
+            self.fifo.triggered = true;
+        }
+        let mut int_source = self.register(Registers::INT_SOURCE);
+        if interrupt_cfg & Bitmasks::LIR == 0 {
+            int_source &= Bitmasks::BOOT_STATUS;
+        }
+        self.set_register(Registers::INT_SOURCE, int_source | source);
+    }
+
+    /// Restore the power-on content of the registers
+    fn power_on_reset(&mut self) {
+        self.registers = [0; REGISTER_SPACE];
+        self.set_register(Registers::WHO_AM_I, WHO_AM_I_VALUE);
+        self.set_register(Registers::CTRL_REG2, Bitmasks::IF_ADD_INC);
+        self.capture_reference = false;
+        self.fifo = Fifo::new();
+        self.odr_time = 0;
+        self.odr_conversions = 0;
+    }
+
+    fn read_24bit(&self, first: Registers) -> i32 {
+        let addr = first.addr() as usize;
+        let bytes = &self.registers[addr..addr + 3];
+        (bytes[2] as i8 as i32) << 16 | (bytes[1] as i32) << 8 | bytes[0] as i32
+    }
+
+    fn write_24bit(&mut self, first: Registers, value: i32) {
+        let addr = first.addr() as usize;
+        self.registers[addr..addr + 3].copy_from_slice(&value.to_le_bytes()[..3]);
+    }
+
+    fn write_register(&mut self, register: Registers, value: u8) {
+        match register {
+            Registers::CTRL_REG1 => {
+                // a new ODR restarts the conversion timing
+                if (value ^ self.register(register)) & Bitmasks::ODR_MASK != 0 {
+                    self.odr_time = 0;
+                    self.odr_conversions = 0;
+                }
+                self.set_register(register, value);
+            }
+            Registers::CTRL_REG2 => {
+                let self_clearing = Bitmasks::BOOT | Bitmasks::SWRESET | Bitmasks::ONE_SHOT;
+                if value & Bitmasks::SWRESET != 0 {
+                    self.power_on_reset();
+                    return;
+                }
+                // BOOT reloads the trimming parameters, which are not simulated
+                self.set_register(register, value & !self_clearing);
+                if value & Bitmasks::FIFO_EN == 0 {
+                    self.fifo.clear();
+                    self.update_fifo_status();
+                }
+                let odr = self.register(Registers::CTRL_REG1) & Bitmasks::ODR_MASK;
+                if value & Bitmasks::ONE_SHOT != 0 && odr == 0 {
+                    self.trigger_conversion();
+                }
+            }
+            Registers::INTERRUPT_CFG => {
+                let previous = self.register(register);
+                let mut stored = value & !(Bitmasks::RESET_AZ | Bitmasks::RESET_ARP);
+                if value & Bitmasks::RESET_AZ != 0 {
+                    stored &= !Bitmasks::AUTOZERO;
+                    self.write_24bit(Registers::REF_P_XL, 0);
+                }
+                if value & Bitmasks::RESET_ARP != 0 {
+                    stored &= !Bitmasks::AUTORIFP;
+                    self.write_24bit(Registers::REF_P_XL, 0);
+                }
+                // the reference is taken at the first conversion after enabling
+                if stored & !previous & (Bitmasks::AUTOZERO | Bitmasks::AUTORIFP) != 0 {
+                    self.capture_reference = true;
+                }
+                self.set_register(register, stored);
+            }
+            Registers::FIFO_CTRL => {
+                // a new configuration waits for a new trigger event, Bypass empties the FIFO
+                self.set_register(register, value);
+                self.fifo.triggered = false;
+                if self.fifo_behaviour() == Behaviour::Bypass {
+                    self.fifo.clear();
+                }
+                self.update_fifo_status();
+            }
+            _ => self.set_register(register, value),
+        }
+    }
+
+    fn read_register(&mut self, addr: u8) -> u8 {
+        let register = match Registers::from_addr(addr) {
+            Some(register) => register,
+            None => return 0,
+        };
+        let value = self.register(register);
+        match register {
+            Registers::INT_SOURCE => {
+                self.set_register(register, value & Bitmasks::BOOT_STATUS);
+            }
+            Registers::PRESS_OUT_H => {
+                let status = self.register(Registers::STATUS) & !(Bitmasks::P_DA | Bitmasks::P_OR);
+                self.set_register(Registers::STATUS, status);
+            }
+            Registers::TEMP_OUT_H => {
+                let status = self.register(Registers::STATUS) & !(Bitmasks::T_DA | Bitmasks::T_OR);
+                self.set_register(Registers::STATUS, status);
+                // the last byte of a sample pops it from the FIFO
+                if self.fifo.len() > 0 {
+                    self.fifo.pop();
+                    self.fifo.overrun = false;
+                    if let Some(front) = self.fifo.front() {
+                        self.write_output(front);
+                    }
+                    self.update_fifo_status();
+                }
+            }
+            _ => {}
+        }
+        value
+    }
+}
+
+impl Interface for SimulatedLps22hb {
+    type Error = SimError;
+
+    fn write(&mut self, addr: u8, value: u8) -> Result<(), Self::Error> {
+        let register = Registers::from_addr(addr).ok_or(SimError::InvalidAddress(addr))?;
+        if !register.is_writable() {
+            return Err(SimError::ReadOnly(register));
+        }
+        self.write_register(register, value);
+        Ok(())
+    }
+
+    fn write_multiple(&mut self, addr: u8, values: &[u8]) -> Result<(), Self::Error> {
+        let increment = self.register(Registers::CTRL_REG2) & Bitmasks::IF_ADD_INC != 0;
+        for (i, value) in values.iter().enumerate() {
+            let addr = if increment {
+                addr.wrapping_add(i as u8)
+            } else {
+                addr
+            };
+            self.write(addr, *value)?;
+        }
+        Ok(())
+    }
+
+    fn read(&mut self, addr: u8, buffer: &mut [u8]) -> Result<(), Self::Error> {
+        Registers::from_addr(addr).ok_or(SimError::InvalidAddress(addr))?;
+        let increment = self.register(Registers::CTRL_REG2) & Bitmasks::IF_ADD_INC != 0;
+        for (i, byte) in buffer.iter_mut().enumerate() {
+            let addr = if increment {
+                addr.wrapping_add(i as u8)
+            } else {
+                addr
+            };
+            *byte = self.read_register(addr);
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,43 @@
+//! Injectable pressure and temperature signals
+
+/// Signal sampled by the simulator at every conversion.
+/// `n` is the index of the conversion, starting at 0.
+#[derive(Debug, Clone, Copy)]
+pub enum Waveform {
+    /// Constant value
+    Constant(f32),
+    /// `start + step * n`
+    Ramp {
+        /// Value of the first sample
+        start: f32,
+        /// Change per sample
+        step: f32,
+    },
+    /// `offset + amplitude * sin(2π n / period)`
+    Sine {
+        /// Mean value
+        offset: f32,
+        /// Peak deviation from the mean
+        amplitude: f32,
+        /// Period in samples
+        period: f32,
+    },
+    /// Values from a function of the sample index
+    Custom(fn(u32) -> f32),
+}
+
+impl Waveform {
+    /// Value of the `n`-th sample
+    pub fn sample(&self, n: u32) -> f32 {
+        match *self {
+            Waveform::Constant(value) => value,
+            Waveform::Ramp { start, step } => start + step * n as f32,
+            Waveform::Sine {
+                offset,
+                amplitude,
+                period,
+            } => offset + amplitude * libm::sinf(2.0 * core::f32::consts::PI * n as f32 / period),
+            Waveform::Custom(f) => f(n),
+        }
+    }
+}
@@ -0,0 +1,169 @@
+//! Configuration snapshot to restore the settings after a reset or power loss
+//!
+//! A [`ConfigSnapshot`] holds the values of all the writable registers. It is
+//! encoded to a fixed array of [`SNAPSHOT_LEN`] bytes, so it can be stored in
+//! the MCU flash:
+//! - format version ([`SNAPSHOT_VERSION`])
+//! - INTERRUPT_CFG, THS_P_L, THS_P_H, CTRL_REG1, CTRL_REG2, CTRL_REG3, FIFO_CTRL,
+//!   REF_P_XL, REF_P_L, REF_P_H, RPDS_L, RPDS_H, RES_CONF
+//! - CRC-8 (polynomial 0x07, initial value 0x00) of the bytes above
+//!
+//! `restore_config` writes the registers in an order that is safe for the
+//! device: power-down first, then LC_EN, FIFO, thresholds, offset and
+//! reference, interrupts, and the ODR last. With AUTOZERO or AUTORIFP enabled
+//! the device captures a new reference pressure at the next conversion.
+//!
+//! ```rust,ignore
+//! let bytes = lps22.save_config()?.to_bytes();
+//! flash.write(SNAPSHOT_ADDR, &bytes);
+//! // after a reset or reflashing
+//! let snapshot = ConfigSnapshot::from_bytes(&stored).unwrap();
+//! lps22.restore_config(&snapshot)?;
+//! ```
+
+use super::*;
+
+/// Version of the encoding
+pub const SNAPSHOT_VERSION: u8 = 1;
+/// Number of registers in a snapshot
+const REGISTER_COUNT: usize = 13;
+/// Length of an encoded snapshot (bytes)
+pub const SNAPSHOT_LEN: usize = REGISTER_COUNT + 2;
+
+/// Registers in a snapshot, in encoding order
+const REGISTERS: [Registers; REGISTER_COUNT] = [
+    Registers::INTERRUPT_CFG,
+    Registers::THS_P_L,
+    Registers::THS_P_H,
+    Registers::CTRL_REG1,
+    Registers::CTRL_REG2,
+    Registers::CTRL_REG3,
+    Registers::FIFO_CTRL,
+    Registers::REF_P_XL,
+    Registers::REF_P_L,
+    Registers::REF_P_H,
+    Registers::RPDS_L,
+    Registers::RPDS_H,
+    Registers::RES_CONF,
+];
+
+/// Registers in restore order (CTRL_REG1 is written in power-down mode first, and with the ODR last)
+const RESTORE_ORDER: [Registers; REGISTER_COUNT - 1] = [
+    Registers::RES_CONF,
+    Registers::CTRL_REG2,
+    Registers::FIFO_CTRL,
+    Registers::THS_P_L,
+    Registers::THS_P_H,
+    Registers::RPDS_L,
+    Registers::RPDS_H,
+    Registers::REF_P_XL,
+    Registers::REF_P_L,
+    Registers::REF_P_H,
+    Registers::CTRL_REG3,
+    Registers::INTERRUPT_CFG,
+];
+
+/// Errors decoding a stored snapshot
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SnapshotError {
+    /// Unknown format version
+    Version(u8),
+    /// The CRC does not match (corrupted or erased storage)
+    Crc,
+}
+
+/// Values of the writable registers
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ConfigSnapshot {
+    values: [u8; REGISTER_COUNT],
+}
+
+impl ConfigSnapshot {
+    /// Value of the register, `None` for registers that are not in the snapshot
+    pub fn get(&self, register: Registers) -> Option<u8> {
+        REGISTERS
+            .iter()
+            .position(|&r| r == register)
+            .map(|index| self.values[index])
+    }
+
+    /// Encode to bytes: version, register values, CRC-8
+    pub fn to_bytes(&self) -> [u8; SNAPSHOT_LEN] {
+        let mut bytes = [0u8; SNAPSHOT_LEN];
+        bytes[0] = SNAPSHOT_VERSION;
+        bytes[1..=REGISTER_COUNT].copy_from_slice(&self.values);
+        bytes[SNAPSHOT_LEN - 1] = crc8(&bytes[..SNAPSHOT_LEN - 1]);
+        bytes
+    }
+
+    /// Decode bytes produced by `to_bytes`, checking the version and the CRC
+    pub fn from_bytes(bytes: &[u8; SNAPSHOT_LEN]) -> Result<Self, SnapshotError> {
+        if crc8(&bytes[..SNAPSHOT_LEN - 1]) != bytes[SNAPSHOT_LEN - 1] {
+            return Err(SnapshotError::Crc);
+        }
+        if bytes[0] != SNAPSHOT_VERSION {
+            return Err(SnapshotError::Version(bytes[0]));
+        }
+        let mut values = [0u8; REGISTER_COUNT];
+        values.copy_from_slice(&bytes[1..=REGISTER_COUNT]);
+        Ok(ConfigSnapshot { values })
+    }
+
+    /// Value of a register of the snapshot
+    fn value(&self, register: Registers) -> u8 {
+        self.get(register).unwrap_or(0)
+    }
+}
+
+/// CRC-8, polynomial 0x07, initial value 0x00
+fn crc8(data: &[u8]) -> u8 {
+    let mut crc = 0u8;
+    for byte in data {
+        crc ^= byte;
+        for _ in 0..8 {
+            crc = if crc & 0x80 != 0 {
+                crc << 1 ^ 0x07
+            } else {
+                crc << 1
+            };
+        }
+    }
+    crc
+}
+
+impl<T, E, MODE> LPS22HB<T, MODE>
+where
+    T: Interface<Error = E>,
+{
+    /// Read the writable registers into a snapshot (the self-clearing bits are left out)
+    pub fn save_config(&mut self) -> Result<ConfigSnapshot, T::Error> {
+        let mut values = [0u8; REGISTER_COUNT];
+        for (value, &register) in values.iter_mut().zip(REGISTERS.iter()) {
+            let data = self.read_register(register)?;
+            *value = match register {
+                Registers::CTRL_REG2 => {
+                    data & !(Bitmasks::BOOT | Bitmasks::SWRESET | Bitmasks::ONE_SHOT)
+                }
+                Registers::INTERRUPT_CFG => data & !(Bitmasks::RESET_AZ | Bitmasks::RESET_ARP),
+                _ => data,
+            };
+        }
+        Ok(ConfigSnapshot { values })
+    }
+}
+
+impl<T, E> LPS22HB<T>
+where
+    T: Interface<Error = E>,
+{
+    /// Write back the registers of a snapshot, one register at a time
+    /// (the snapshot may change the address auto-increment setting)
+    pub fn restore_config(&mut self, snapshot: &ConfigSnapshot) -> Result<(), T::Error> {
+        let ctrl_reg1 = snapshot.value(Registers::CTRL_REG1);
+        self.write_register(Registers::CTRL_REG1, ctrl_reg1 & !Bitmasks::ODR_MASK)?;
+        for &register in RESTORE_ORDER.iter() {
+            self.write_register(register, snapshot.value(register))?;
+        }
+        self.write_register(Registers::CTRL_REG1, ctrl_reg1)
+    }
+}
@@ -0,0 +1,156 @@
+//! Storm warning based on the hardware differential pressure interrupt
+//!
+//! The alarm combines the threshold registers, the AUTORIFP function and the
+//! low pressure interrupt: the sensor captures a reference pressure and raises
+//! INT_DRDY when the pressure drops more than the threshold below it,
+//! so the MCU can sleep in the meantime.
+//! The reference is refreshed every half `refresh_interval`, so that slow drift
+//! does not trigger the alarm. At each refresh the threshold is lowered by the
+//! pressure already lost since the previous reference was captured, so that a
+//! drop straddling a refresh is still detected: a drop of `drop` within half
+//! `refresh_interval` always triggers the alarm, a steady fall slower than
+//! `drop` per `refresh_interval` never does.
+
+use super::*;
+use interrupt::InterruptConfig;
+
+/// Storm alarm settings
+#[derive(Debug, Clone, Copy)]
+pub struct StormAlarmConfig {
+    /// Pressure drop (hPa) below the reference that triggers the alarm,
+    /// rounded to the 1/16 hPa resolution of the threshold
+    pub drop: f32,
+    /// Data rate used while the alarm is armed
+    pub odr: ODR,
+    /// Interval in seconds over which a pressure drop is evaluated
+    /// (the reference pressure is refreshed every half interval)
+    pub refresh_interval: u32,
+    /// Interrupt pin polarity
+    pub active_high_or_low: INT_ACTIVE,
+    /// Interrupt pin configuration
+    pub pushpull_or_opendrain: INT_PIN,
+}
+
+impl Default for StormAlarmConfig {
+    fn default() -> Self {
+        StormAlarmConfig {
+            drop: 3.0,                  // 3 hPa...
+            refresh_interval: 3 * 3600, // ...in 3 hours
+            odr: ODR::_1Hz,
+            active_high_or_low: INT_ACTIVE::High,
+            pushpull_or_opendrain: INT_PIN::PushPull,
+        }
+    }
+}
+
+/// Result of polling the storm alarm
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum StormEvent {
+    /// Alarm not armed
+    Disarmed,
+    /// Nothing happened
+    Quiet,
+    /// The reference pressure was refreshed
+    ReferenceRefreshed,
+    /// The pressure dropped below the reference by more than the threshold
+    PressureDrop,
+}
+
+/// Storm alarm state
+#[derive(Debug, Clone)]
+pub struct StormAlarm {
+    config: StormAlarmConfig,
+    armed: bool,
+    last_refresh: u32,
+}
+
+impl StormAlarm {
+    /// Create a disarmed alarm
+    pub fn new(config: StormAlarmConfig) -> Self {
+        StormAlarm {
+            config,
+            armed: false,
+            last_refresh: 0,
+        }
+    }
+
+    /// Is the alarm armed?
+    pub fn is_armed(&self) -> bool {
+        self.armed
+    }
+
+    /// Program the threshold, the low pressure interrupt and AUTORIFP,
+    /// and start continuous measurements. `now` is the current time in seconds.
+    pub fn arm<T, E>(&mut self, sensor: &mut LPS22HB<T>, now: u32) -> Result<(), E>
+    where
+        T: Interface<Error = E>,
+    {
+        sensor.configure_interrupts(InterruptConfig {
+            active_high_or_low: self.config.active_high_or_low,
+            pushpull_or_opendrain: self.config.pushpull_or_opendrain,
+            data_signal_config: INT_DRDY::P_low,
+            enable_latch_interrupt: FLAG::Enabled,
+            enable_low_event: FLAG::Enabled,
+            enable_differential: FLAG::Enabled,
+            ..Default::default()
+        })?;
+        sensor.set_datarate(self.config.odr)?;
+        self.refresh_reference(sensor, now)?;
+        // clear an interrupt latched before arming
+        sensor.get_int_status()?;
+        self.armed = true;
+        Ok(())
+    }
+
+    /// Disable the interrupt and AUTORIFP. The data rate is left unchanged.
+    pub fn disarm<T, E>(&mut self, sensor: &mut LPS22HB<T>) -> Result<(), E>
+    where
+        T: Interface<Error = E>,
+    {
+        sensor.configure_interrupts(InterruptConfig::default())?;
+        sensor.autorifp_config(false)?;
+        sensor.autorifp_reset()?;
+        self.armed = false;
+        Ok(())
+    }
+
+    /// Check for a pressure drop (this clears the latched interrupt) and
+    /// refresh the reference when it is due. `now` is the current time in seconds.
+    pub fn poll<T, E>(&mut self, sensor: &mut LPS22HB<T>, now: u32) -> Result<StormEvent, E>
+    where
+        T: Interface<Error = E>,
+    {
+        if !self.armed {
+            return Ok(StormEvent::Disarmed);
+        }
+        if sensor.get_int_status()?.diff_press_low {
+            return Ok(StormEvent::PressureDrop);
+        }
+        if now.wrapping_sub(self.last_refresh) >= self.config.refresh_interval / 2 {
+            self.refresh_reference(sensor, now)?;
+            return Ok(StormEvent::ReferenceRefreshed);
+        }
+        Ok(StormEvent::Quiet)
+    }
+
+    /// Capture the current pressure as the new reference and program the threshold.
+    /// `now` is the current time in seconds.
+    pub fn refresh_reference<T, E>(&mut self, sensor: &mut LPS22HB<T>, now: u32) -> Result<(), E>
+    where
+        T: Interface<Error = E>,
+    {
+        let mut threshold = self.config.drop * OFFSET_SCALE;
+        if self.armed {
+            // the pressure lost since the reference in use was captured counts towards the drop
+            let lost = sensor.read_reference_pressure()? - sensor.read_pressure()?;
+            if lost > 0.0 {
+                threshold = (threshold - lost * OFFSET_SCALE).max(1.0);
+            }
+        }
+        sensor.set_threshold_raw(libm::roundf(threshold) as u16)?;
+        sensor.autorifp_reset()?;
+        sensor.autorifp_config(true)?;
+        self.last_refresh = now;
+        Ok(())
+    }
+}
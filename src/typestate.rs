@@ -0,0 +1,284 @@
+//! Typestate API enforcing the power mode preconditions at compile time
+//!
+//! The driver type carries the power mode, and the transitions consume the
+//! driver and return it in the new mode. Operations that are only valid in
+//! some modes only exist there:
+//! - [`PowerDown`]: enabling low-power mode, software reset, starting a one-shot measurement
+//! - [`OneShot`]: waiting for and reading the one-shot measurement, which is only
+//!   returned once the conversion is complete
+//! - [`Continuous`]: changing the output data rate, low-pass filter
+//! - [`PowerDown`] and [`Continuous`]: changing the power mode, reboot (not while a
+//!   one-shot measurement is running)
+//!
+//! - every mode but [`OneShot`] ([`Configurable`]): configuring the FIFO, the interrupts
+//!   and the AUTOZERO / AUTORIFP functions, which would change the FIFO contents or the
+//!   reference pressure of a running conversion
+//!
+//! The functions that are valid in every mode (reading data, FIFO status...)
+//! are available in all of them. The untyped API ([`Untyped`], the default mode)
+//! remains available, and the driver can move between the two.
+//!
+//! ```rust,ignore
+//! let lps22 = LPS22HB::new(i2c_interface).into_power_down().unwrap();
+//! let mut lps22 = lps22.start_one_shot().unwrap();
+//! let (lps22, measurement) = loop {
+//!     match lps22.finish() {
+//!         Ok(done) => break done,
+//!         Err(ModeChangeError { error: nb::Error::WouldBlock, driver }) => lps22 = driver,
+//!         Err(ModeChangeError { error: nb::Error::Other(e), .. }) => return Err(e),
+//!     }
+//! };
+//! let mut lps22 = lps22.into_continuous(ContinuousRate::_25Hz).unwrap();
+//! lps22.lowpass_filter(true, false)?;
+//! ```
+
+use super::*;
+use crate::sensor::Measurement;
+use core::fmt;
+
+/// Mode of the untyped API: the power mode is not tracked
+#[derive(Debug, Clone, Copy)]
+pub struct Untyped;
+
+/// Power-down mode (ODR = 000), no measurement in progress
+#[derive(Debug, Clone, Copy)]
+pub struct PowerDown;
+
+/// One-shot measurement started from power-down mode.
+/// The power mode cannot be changed while the measurement is running:
+///
+/// ```compile_fail
+/// use lps22hb::sim::SimulatedLps22hb;
+/// use lps22hb::{PowerMode, LPS22HB};
+///
+/// let lps22 = LPS22HB::new(SimulatedLps22hb::new()).into_power_down().unwrap();
+/// let mut lps22 = lps22.start_one_shot().unwrap();
+/// lps22.set_power_mode(PowerMode::LowCurrent).unwrap();
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct OneShot;
+
+/// Continuous mode (ODR > 000)
+#[derive(Debug, Clone, Copy)]
+pub struct Continuous;
+
+mod sealed {
+    pub trait Sealed {}
+
+    impl Sealed for super::Untyped {}
+    impl Sealed for super::PowerDown {}
+    impl Sealed for super::Continuous {}
+}
+
+/// Modes in which the FIFO, the interrupts and the AUTOZERO / AUTORIFP functions
+/// can be configured: every mode but [`OneShot`]
+///
+/// ```compile_fail
+/// use lps22hb::sim::SimulatedLps22hb;
+/// use lps22hb::LPS22HB;
+///
+/// let lps22 = LPS22HB::new(SimulatedLps22hb::new()).into_power_down().unwrap();
+/// let mut lps22 = lps22.start_one_shot().unwrap();
+/// lps22.autozero_config(true).unwrap();
+/// ```
+pub trait Configurable: sealed::Sealed {}
+
+impl Configurable for Untyped {}
+impl Configurable for PowerDown {}
+impl Configurable for Continuous {}
+
+/// Failed mode transition: the bus error and the driver, still in its previous mode
+pub struct ModeChangeError<E, D> {
+    /// Interface error
+    pub error: E,
+    /// Driver instance in the mode it had before the transition
+    pub driver: D,
+}
+
+impl<E: fmt::Debug, D> fmt::Debug for ModeChangeError<E, D> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ModeChangeError")
+            .field("error", &self.error)
+            .finish()
+    }
+}
+
+/// Output data rates of the continuous mode
+#[allow(non_camel_case_types)]
+#[derive(Debug, Clone, Copy)]
+pub enum ContinuousRate {
+    /// 1 Hz
+    _1Hz,
+    /// 10 Hz
+    _10Hz,
+    /// 25 Hz
+    _25Hz,
+    /// 50 Hz
+    _50Hz,
+    /// 75 Hz
+    _75Hz,
+}
+
+impl From<ContinuousRate> for ODR {
+    fn from(rate: ContinuousRate) -> Self {
+        match rate {
+            ContinuousRate::_1Hz => ODR::_1Hz,
+            ContinuousRate::_10Hz => ODR::_10Hz,
+            ContinuousRate::_25Hz => ODR::_25Hz,
+            ContinuousRate::_50Hz => ODR::_50Hz,
+            ContinuousRate::_75Hz => ODR::_75Hz,
+        }
+    }
+}
+
+/// Result of a mode transition
+pub type Transition<T, E, FROM, TO> = Result<LPS22HB<T, TO>, ModeChangeError<E, LPS22HB<T, FROM>>>;
+
+/// Result of `finish`: the driver back in power-down mode and the one-shot measurement
+pub type Finish<T, E> =
+    Result<(LPS22HB<T, PowerDown>, Measurement), ModeChangeError<nb::Error<E>, LPS22HB<T, OneShot>>>;
+
+impl<T, E, MODE> LPS22HB<T, MODE>
+where
+    T: Interface<Error = E>,
+{
+    /// Run `f`, then move into mode `NEW`, or give the driver back in its current mode
+    fn transition<NEW, F>(mut self, f: F) -> Transition<T, E, MODE, NEW>
+    where
+        F: FnOnce(&mut Self) -> Result<(), E>,
+    {
+        match f(&mut self) {
+            Ok(()) => Ok(self.into_mode()),
+            Err(error) => Err(ModeChangeError {
+                error,
+                driver: self,
+            }),
+        }
+    }
+}
+
+impl<T, E> LPS22HB<T, Untyped>
+where
+    T: Interface<Error = E>,
+{
+    /// Switch into power-down mode and start tracking the power mode
+    pub fn into_power_down(self) -> Transition<T, E, Untyped, PowerDown> {
+        self.transition(|lps22| lps22.write_datarate(ODR::PowerDown))
+    }
+}
+
+impl<T, E> LPS22HB<T, PowerDown>
+where
+    T: Interface<Error = E>,
+{
+    /// Enable low-power mode
+    pub fn enable_low_power(&mut self) -> Result<(), T::Error> {
+        self.write_low_power()
+    }
+
+    /// Run software reset (the device stays in power-down mode)
+    pub fn software_reset(&mut self) -> Result<(), T::Error> {
+        self.write_software_reset()
+    }
+
+    /// Set the power mode (low-noise or low-current)
+    pub fn set_power_mode(&mut self, mode: PowerMode) -> Result<(), T::Error> {
+        self.write_power_mode(mode)
+    }
+
+    /// Reboot, refreshing the trimming registers from the Flash memory block
+    pub fn reboot(&mut self) -> Result<(), T::Error> {
+        self.write_reboot()
+    }
+
+    /// Start a one-shot measurement. An unread sample is discarded first,
+    /// so that `finish` waits for this acquisition.
+    pub fn start_one_shot(self) -> Transition<T, E, PowerDown, OneShot> {
+        self.transition(|lps22| lps22.write_one_shot())
+    }
+
+    /// Switch into continuous mode at the given output data rate
+    pub fn into_continuous(self, rate: ContinuousRate) -> Transition<T, E, PowerDown, Continuous> {
+        self.transition(|lps22| lps22.write_datarate(rate.into()))
+    }
+
+    /// Stop tracking the power mode
+    pub fn into_untyped(self) -> LPS22HB<T> {
+        self.into_mode()
+    }
+}
+
+impl<T, E> LPS22HB<T, OneShot>
+where
+    T: Interface<Error = E>,
+{
+    /// Are the pressure and temperature of the one-shot measurement available?
+    pub fn is_ready(&mut self) -> Result<bool, T::Error> {
+        let status = self.read_register(Registers::STATUS)?;
+        Ok(status & (Bitmasks::P_DA | Bitmasks::T_DA) == Bitmasks::P_DA | Bitmasks::T_DA)
+    }
+
+    /// Read the measurement and return to power-down mode.
+    /// Fails with `nb::Error::WouldBlock`, giving the driver back, until both the pressure
+    /// and the temperature of the one-shot measurement are available.
+    pub fn finish(mut self) -> Finish<T, E> {
+        let result = match self.is_ready() {
+            Ok(true) => self.read_measurement().map_err(nb::Error::Other),
+            Ok(false) => Err(nb::Error::WouldBlock),
+            Err(error) => Err(nb::Error::Other(error)),
+        };
+        match result {
+            Ok(measurement) => Ok((self.into_mode(), measurement)),
+            Err(error) => Err(ModeChangeError {
+                error,
+                driver: self,
+            }),
+        }
+    }
+}
+
+impl<T, E> LPS22HB<T, Continuous>
+where
+    T: Interface<Error = E>,
+{
+    /// Change the output data rate
+    pub fn set_rate(&mut self, rate: ContinuousRate) -> Result<(), T::Error> {
+        self.write_datarate(rate.into())
+    }
+
+    /// Enable and configure low-pass filter on pressure data
+    pub fn lowpass_filter(&mut self, enable: bool, configure: bool) -> Result<(), T::Error> {
+        self.write_lowpass_filter(enable, configure)
+    }
+
+    /// Set the low-pass filter on pressure data, optionally resetting it
+    pub fn set_lowpass_filter(&mut self, filter: LowPassFilter, reset: bool) -> Result<(), T::Error> {
+        self.write_lowpass_setting(filter, reset)
+    }
+
+    /// Reset low-pass filter (read this register before generating pressure measurements)
+    pub fn lowpass_filter_reset(&mut self) -> Result<(), T::Error> {
+        self.read_lowpass_filter_reset()
+    }
+
+    /// Set the power mode (low-noise or low-current),
+    /// going through power-down mode and back to the current data rate
+    pub fn set_power_mode(&mut self, mode: PowerMode) -> Result<(), T::Error> {
+        self.write_power_mode(mode)
+    }
+
+    /// Reboot, refreshing the trimming registers from the Flash memory block
+    pub fn reboot(&mut self) -> Result<(), T::Error> {
+        self.write_reboot()
+    }
+
+    /// Stop the measurements and switch into power-down mode
+    pub fn into_power_down(self) -> Transition<T, E, Continuous, PowerDown> {
+        self.transition(|lps22| lps22.write_datarate(ODR::PowerDown))
+    }
+
+    /// Stop tracking the power mode (the device keeps measuring)
+    pub fn into_untyped(self) -> LPS22HB<T> {
+        self.into_mode()
+    }
+}
@@ -0,0 +1,147 @@
+//! Vertical speed (variometer) estimation from consecutive pressure readings
+//!
+//! Altitude is derived from pressure with the standard atmosphere and smoothed
+//! with an alpha-beta filter, which also tracks the vertical speed.
+//! Feed it with readings taken at the configured data rate, either one at a time
+//! in continuous mode or in bursts read from the FIFO.
+
+use super::*;
+use altitude::{altitude_from_pressure, STANDARD_PRESSURE};
+use sensor::Measurement;
+
+/// Variometer settings
+#[derive(Debug, Clone, Copy)]
+pub struct VariometerConfig {
+    /// Altitude gain of the alpha-beta filter (0..1, smaller is smoother)
+    pub alpha: f32,
+    /// Speed gain of the alpha-beta filter (0..alpha, smaller is smoother)
+    pub beta: f32,
+    /// Pressure in hPa used as the zero altitude reference
+    pub reference_pressure: f32,
+}
+
+impl Default for VariometerConfig {
+    fn default() -> Self {
+        VariometerConfig::critically_damped(0.05)
+    }
+}
+
+impl VariometerConfig {
+    /// Settings for a critically damped filter with the given altitude gain,
+    /// relative to the standard sea-level pressure
+    pub fn critically_damped(alpha: f32) -> Self {
+        VariometerConfig {
+            alpha,
+            beta: alpha * alpha / (2.0 - alpha),
+            reference_pressure: STANDARD_PRESSURE,
+        }
+    }
+}
+
+/// Filtered altitude and vertical speed
+#[derive(Debug, Clone, Copy, Default)]
+pub struct VarioOutput {
+    /// Altitude in metres above the reference pressure level
+    pub altitude: f32,
+    /// Vertical speed in m/s, positive when climbing
+    pub vertical_speed: f32,
+}
+
+/// Alpha-beta altitude and vertical speed estimator
+#[derive(Debug, Clone)]
+pub struct Variometer {
+    config: VariometerConfig,
+    period: f32,
+    state: Option<VarioOutput>,
+}
+
+impl Variometer {
+    /// Create a variometer for samples produced at the given data rate.
+    /// Returns `None` for `ODR::PowerDown`, use [`Variometer::with_period`] with one-shot readings.
+    pub fn new(odr: ODR, config: VariometerConfig) -> Option<Self> {
+        odr.period().map(|period| Self::with_period(period, config))
+    }
+
+    /// Create a variometer for samples taken every `period` seconds
+    pub fn with_period(period: f32, config: VariometerConfig) -> Self {
+        Variometer {
+            config,
+            period,
+            state: None,
+        }
+    }
+
+    /// Feed a pressure reading (hPa) and return the updated estimate
+    pub fn update(&mut self, pressure: f32) -> VarioOutput {
+        let measured = altitude_from_pressure(pressure, self.config.reference_pressure);
+        let output = match self.state {
+            None => VarioOutput {
+                altitude: measured,
+                vertical_speed: 0.0,
+            },
+            Some(previous) => {
+                let predicted = previous.altitude + previous.vertical_speed * self.period;
+                let residual = measured - predicted;
+                VarioOutput {
+                    altitude: predicted + self.config.alpha * residual,
+                    vertical_speed: previous.vertical_speed
+                        + self.config.beta * residual / self.period,
+                }
+            }
+        };
+        self.state = Some(output);
+        output
+    }
+
+    /// Advance the estimate by one sample period without a measurement,
+    /// e.g. to skip a reading rejected as a glitch
+    pub fn predict(&mut self) -> Option<VarioOutput> {
+        let period = self.period;
+        self.state.as_mut().map(|state| {
+            state.altitude += state.vertical_speed * period;
+            *state
+        })
+    }
+
+    /// Move the altitude to a pressure reading (hPa), keeping the vertical speed,
+    /// e.g. to accept a step in the readings after rejecting them as a glitch
+    pub fn reset_altitude(&mut self, pressure: f32) -> VarioOutput {
+        let altitude = altitude_from_pressure(pressure, self.config.reference_pressure);
+        let output = VarioOutput {
+            altitude,
+            vertical_speed: self.state.map_or(0.0, |state| state.vertical_speed),
+        };
+        self.state = Some(output);
+        output
+    }
+
+    /// Feed a burst of samples (e.g. read with `read_fifo()`), oldest first, and return the latest estimate
+    pub fn update_burst(&mut self, samples: &[Measurement]) -> Option<VarioOutput> {
+        samples.iter().map(|s| self.update(s.pressure)).last()
+    }
+
+    /// Latest estimate, if any sample was fed
+    pub fn output(&self) -> Option<VarioOutput> {
+        self.state
+    }
+
+    /// Sample interval in seconds
+    pub fn period(&self) -> f32 {
+        self.period
+    }
+
+    /// Set the zero altitude reference (hPa), keeping the filter state consistent
+    pub fn set_reference_pressure(&mut self, reference_pressure: f32) {
+        if let Some(state) = self.state.as_mut() {
+            let pressure =
+                altitude::pressure_at_altitude(state.altitude, self.config.reference_pressure);
+            state.altitude = altitude_from_pressure(pressure, reference_pressure);
+        }
+        self.config.reference_pressure = reference_pressure;
+    }
+
+    /// Forget the filter state
+    pub fn reset(&mut self) {
+        self.state = None;
+    }
+}
@@ -0,0 +1,297 @@
+//! Pressure tendency and short-term weather forecast
+//!
+//! Readings are stored as sea-level pressure (QNH) in a fixed-size history
+//! (no allocation). From the history the module derives:
+//! - the 3-hour pressure tendency with its WMO characteristic (code table 0200)
+//! - a rising / steady / falling classification
+//! - a Zambretti short-term forecast
+
+use super::*;
+use reduction::Station;
+
+/// Tendency period, 3 hours in seconds
+const TENDENCY_PERIOD: u32 = 3 * 3600;
+
+/// Maximum distance of a stored sample from the requested time, in seconds
+const SAMPLE_TOLERANCE: u32 = 30 * 60;
+
+/// Pressure changes smaller than this (hPa) count as "the same" for the WMO characteristic
+const SAME_PRESSURE: f32 = 0.1;
+
+/// Default band (hPa per 3 hours) within which the pressure is considered steady
+pub const STEADY_BAND: f32 = 1.6;
+
+/// Rising / steady / falling classification of the pressure tendency
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Trend {
+    /// Pressure rising
+    Rising,
+    /// Pressure steady
+    Steady,
+    /// Pressure falling
+    Falling,
+}
+
+impl Trend {
+    /// Classify a 3-hour pressure change (hPa) using the given steady band
+    pub fn from_change(change: f32, steady_band: f32) -> Self {
+        if change >= steady_band {
+            Trend::Rising
+        } else if change <= -steady_band {
+            Trend::Falling
+        } else {
+            Trend::Steady
+        }
+    }
+}
+
+/// Characteristic of pressure tendency (WMO code table 0200)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Characteristic {
+    /// 0: increasing, then decreasing; pressure the same or higher than 3 hours ago
+    IncreasingThenDecreasing = 0,
+    /// 1: increasing, then steady, or increasing then increasing more slowly
+    IncreasingThenSteady = 1,
+    /// 2: increasing (steadily or unsteadily)
+    Increasing = 2,
+    /// 3: decreasing or steady, then increasing, or increasing then increasing more rapidly
+    SteadyThenIncreasing = 3,
+    /// 4: steady; pressure the same as 3 hours ago
+    Steady = 4,
+    /// 5: decreasing, then increasing; pressure the same or lower than 3 hours ago
+    DecreasingThenIncreasing = 5,
+    /// 6: decreasing, then steady, or decreasing then decreasing more slowly
+    DecreasingThenSteady = 6,
+    /// 7: decreasing (steadily or unsteadily)
+    Decreasing = 7,
+    /// 8: steady or increasing, then decreasing, or decreasing then decreasing more rapidly
+    SteadyThenDecreasing = 8,
+}
+
+impl Characteristic {
+    /// WMO code figure
+    pub fn code(self) -> u8 {
+        self as u8
+    }
+
+    /// Derive the characteristic from the changes over the first and the second half of the period (hPa)
+    pub fn from_changes(first_half: f32, second_half: f32) -> Self {
+        let total = first_half + second_half;
+        let up = |x: f32| x >= SAME_PRESSURE;
+        let down = |x: f32| x <= -SAME_PRESSURE;
+
+        if !up(total) && !down(total) {
+            if up(first_half) && down(second_half) {
+                Characteristic::IncreasingThenDecreasing
+            } else if down(first_half) && up(second_half) {
+                Characteristic::DecreasingThenIncreasing
+            } else {
+                Characteristic::Steady
+            }
+        } else if up(total) {
+            if down(second_half) {
+                Characteristic::IncreasingThenDecreasing
+            } else if !up(first_half) || second_half > 2.0 * first_half {
+                Characteristic::SteadyThenIncreasing
+            } else if !up(second_half) || first_half > 2.0 * second_half {
+                Characteristic::IncreasingThenSteady
+            } else {
+                Characteristic::Increasing
+            }
+        } else if up(second_half) {
+            Characteristic::DecreasingThenIncreasing
+        } else if !down(first_half) || second_half < 2.0 * first_half {
+            Characteristic::SteadyThenDecreasing
+        } else if !down(second_half) || first_half < 2.0 * second_half {
+            Characteristic::DecreasingThenSteady
+        } else {
+            Characteristic::Decreasing
+        }
+    }
+}
+
+/// 3-hour pressure tendency
+#[derive(Debug, Clone, Copy)]
+pub struct Tendency {
+    /// Sea-level pressure change over the last 3 hours, in hPa
+    pub change: f32,
+    /// WMO characteristic of the change
+    pub characteristic: Characteristic,
+    /// Rising / steady / falling classification
+    pub trend: Trend,
+}
+
+/// Zambretti forecast
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Forecast {
+    /// Forecast letter, 'A' (settled fine) to 'Z' (stormy, much rain)
+    pub letter: char,
+}
+
+impl Forecast {
+    /// Zambretti forecast for a sea-level pressure (hPa) and trend
+    pub fn zambretti(sea_level_pressure: f32, trend: Trend) -> Self {
+        const FALLING: &[u8] = b"ABDHORUXZ";
+        const STEADY: &[u8] = b"ABEKNPSWXZ";
+        const RISING: &[u8] = b"ABCFGIJLMQTYZ";
+
+        let (table, z) = match trend {
+            Trend::Falling => (FALLING, 127.0 - 0.12 * sea_level_pressure),
+            Trend::Steady => (STEADY, 144.0 - 0.13 * sea_level_pressure - 9.0),
+            Trend::Rising => (RISING, 185.0 - 0.16 * sea_level_pressure - 19.0),
+        };
+        let index = (libm::roundf(z) as i32 - 1).clamp(0, table.len() as i32 - 1) as usize;
+        Forecast {
+            letter: table[index] as char,
+        }
+    }
+
+    /// Forecast text
+    pub fn description(&self) -> &'static str {
+        match self.letter {
+            'A' => "Settled fine",
+            'B' => "Fine weather",
+            'C' => "Becoming fine",
+            'D' => "Fine, becoming less settled",
+            'E' => "Fine, possible showers",
+            'F' => "Fairly fine, improving",
+            'G' => "Fairly fine, possible showers early",
+            'H' => "Fairly fine, showery later",
+            'I' => "Showery early, improving",
+            'J' => "Changeable, mending",
+            'K' => "Fairly fine, showers likely",
+            'L' => "Rather unsettled, clearing later",
+            'M' => "Unsettled, probably improving",
+            'N' => "Showery, bright intervals",
+            'O' => "Showery, becoming less settled",
+            'P' => "Changeable, some rain",
+            'Q' => "Unsettled, short fine intervals",
+            'R' => "Unsettled, rain later",
+            'S' => "Unsettled, rain at times",
+            'T' => "Very unsettled, finer at times",
+            'U' => "Rain at times, worse later",
+            'V' => "Rain at times, becoming very unsettled",
+            'W' => "Rain at frequent intervals",
+            'X' => "Rain, very unsettled",
+            'Y' => "Stormy, may improve",
+            _ => "Stormy, much rain",
+        }
+    }
+}
+
+/// Sea-level pressure sample
+#[derive(Debug, Clone, Copy, Default)]
+struct Sample {
+    /// Time in seconds (any monotonic clock)
+    timestamp: u32,
+    /// Sea-level pressure in hPa
+    pressure: f32,
+}
+
+/// Fixed-size history of sea-level pressure readings, holding the last `N` samples.
+/// `N` must cover at least 3 hours at the chosen reading interval
+/// (e.g. 64 samples every 5 minutes).
+#[derive(Debug, Clone)]
+pub struct PressureHistory<const N: usize> {
+    samples: [Sample; N],
+    head: usize,
+    len: usize,
+    station: Station,
+    steady_band: f32,
+}
+
+impl<const N: usize> PressureHistory<N> {
+    const NON_EMPTY: () = assert!(N > 0, "PressureHistory needs at least one sample");
+
+    /// Create an empty history for a station at `station_altitude` metres
+    pub fn new(station_altitude: f32) -> Self {
+        let () = Self::NON_EMPTY;
+        PressureHistory {
+            samples: [Sample::default(); N],
+            head: 0,
+            len: 0,
+            station: Station::new(station_altitude),
+            steady_band: STEADY_BAND,
+        }
+    }
+
+    /// Change the band (hPa per 3 hours) within which the pressure is considered steady
+    pub fn set_steady_band(&mut self, steady_band: f32) {
+        self.steady_band = steady_band;
+    }
+
+    /// Add a station pressure reading (hPa, e.g. from `read_pressure()`) taken at `timestamp` seconds.
+    /// Returns the sea-level pressure (QNH) that was stored.
+    pub fn add(&mut self, timestamp: u32, station_pressure: f32) -> f32 {
+        let pressure = self.station.qnh(station_pressure);
+        self.samples[self.head] = Sample {
+            timestamp,
+            pressure,
+        };
+        self.head = (self.head + 1) % N;
+        self.len = (self.len + 1).min(N);
+        pressure
+    }
+
+    /// Number of stored samples
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Is the history empty?
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Remove all samples
+    pub fn clear(&mut self) {
+        self.len = 0;
+        self.head = 0;
+    }
+
+    /// Latest sea-level pressure in hPa
+    pub fn latest(&self) -> Option<f32> {
+        self.latest_sample().map(|s| s.pressure)
+    }
+
+    fn latest_sample(&self) -> Option<Sample> {
+        match self.len {
+            0 => None,
+            _ => Some(self.samples[(self.head + N - 1) % N]),
+        }
+    }
+
+    /// Sample closest to `age` seconds before the latest one, within the tolerance
+    fn sample_aged(&self, age: u32) -> Option<Sample> {
+        let latest = self.latest_sample()?;
+        (0..self.len)
+            .map(|i| self.samples[(self.head + N - 1 - i) % N])
+            .map(|s| (latest.timestamp.wrapping_sub(s.timestamp), s))
+            .map(|(a, s)| (a.abs_diff(age), s))
+            .filter(|(distance, _)| *distance <= SAMPLE_TOLERANCE)
+            .min_by_key(|(distance, _)| *distance)
+            .map(|(_, s)| s)
+    }
+
+    /// 3-hour tendency, `None` until the history covers 3 hours
+    pub fn tendency(&self) -> Option<Tendency> {
+        let now = self.latest_sample()?;
+        let start = self.sample_aged(TENDENCY_PERIOD)?;
+        let middle = self.sample_aged(TENDENCY_PERIOD / 2)?;
+        let change = now.pressure - start.pressure;
+        Some(Tendency {
+            change,
+            characteristic: Characteristic::from_changes(
+                middle.pressure - start.pressure,
+                now.pressure - middle.pressure,
+            ),
+            trend: Trend::from_change(change, self.steady_band),
+        })
+    }
+
+    /// Zambretti forecast from the latest sea-level pressure and the 3-hour trend
+    pub fn forecast(&self) -> Option<Forecast> {
+        let tendency = self.tendency()?;
+        Some(Forecast::zambretti(self.latest()?, tendency.trend))
+    }
+}
@@ -0,0 +1,107 @@
+mod common;
+
+use common::{CountingDelay, NoDelay, SharedSim, StuckOneShot};
+use embedded_hal::blocking::delay::DelayMs;
+use lps22hb::average::{
+    AveragedMeasurement, MeasureError, OutlierRejection, Statistics, MAX_SAMPLES,
+};
+use lps22hb::sensor::Measurement;
+use lps22hb::sim::{SimulatedLps22hb, Waveform};
+use lps22hb::*;
+
+#[test]
+fn statistics_of_samples() {
+    let stats = Statistics::from_samples(&[1.0, 2.0, 3.0, 4.0], OutlierRejection::Disabled);
+    assert_eq!(stats.count, 4);
+    assert_eq!(stats.mean, 2.5);
+    assert_eq!((stats.min, stats.max), (1.0, 4.0));
+    assert!((stats.std_dev - 1.2910).abs() < 1e-4);
+
+    let empty = Statistics::from_samples(&[], OutlierRejection::Sigma(2.0));
+    assert_eq!(empty.count, 0);
+}
+
+#[test]
+fn outliers_are_rejected() {
+    let samples = [10.0, 10.2, 9.8, 10.1, 9.9, 10.0, 10.2, 9.8, 10.1, 30.0];
+    let all = Statistics::from_samples(&samples, OutlierRejection::Disabled);
+    assert_eq!(all.count, 10);
+    assert_eq!(all.max, 30.0);
+
+    let kept = Statistics::from_samples(&samples, OutlierRejection::Sigma(2.0));
+    assert_eq!(kept.count, 9);
+    assert_eq!(kept.max, 10.2);
+    assert!((kept.mean - 10.0111).abs() < 1e-3);
+
+    // constant samples have no outliers
+    let flat = Statistics::from_samples(&[5.0; 4], OutlierRejection::Sigma(1.0));
+    assert_eq!((flat.count, flat.std_dev), (4, 0.0));
+}
+
+#[test]
+fn measure_averaged_one_shots() {
+    let sim = SimulatedLps22hb::with_waveforms(
+        Waveform::Ramp {
+            start: 1000.0,
+            step: 1.0,
+        },
+        Waveform::Constant(25.0),
+    );
+    let mut lps22 = LPS22HB::new(sim);
+    let result = lps22
+        .measure_averaged(5, &mut NoDelay, OutlierRejection::Disabled)
+        .unwrap();
+    assert_eq!(result.pressure.count, 5);
+    assert_eq!(result.pressure.mean, 1002.0);
+    assert_eq!(result.temperature.mean, 25.0);
+    assert_eq!(lps22.destroy().conversions(), 5);
+}
+
+#[test]
+fn measure_averaged_times_out() {
+    let mut lps22 = LPS22HB::new(StuckOneShot(SimulatedLps22hb::new()));
+    let mut delay = CountingDelay::default();
+    let result = lps22.measure_averaged(4, &mut delay, OutlierRejection::Disabled);
+    assert_eq!(result.unwrap_err(), MeasureError::Timeout);
+    assert_eq!(delay.0, 50);
+}
+
+#[test]
+fn unread_continuous_sample_is_not_averaged() {
+    let sim = SharedSim::new(SimulatedLps22hb::new());
+    let mut lps22 = LPS22HB::new(StuckOneShot(sim.clone()));
+    lps22.set_datarate(ODR::_75Hz).unwrap();
+    // one conversion, left unread
+    sim.clock().delay_ms(20);
+    assert_eq!(sim.0.borrow().conversions(), 1);
+
+    let result = lps22.measure_averaged(1, &mut NoDelay, OutlierRejection::Disabled);
+    assert_eq!(result.unwrap_err(), MeasureError::Timeout);
+}
+
+#[test]
+fn sample_count_is_checked() {
+    let mut lps22 = LPS22HB::new(SimulatedLps22hb::new());
+    for n in [0, MAX_SAMPLES + 1] {
+        assert_eq!(
+            lps22
+                .measure_averaged(n, &mut NoDelay, OutlierRejection::Disabled)
+                .unwrap_err(),
+            MeasureError::InvalidSampleCount
+        );
+        assert_eq!(
+            lps22
+                .measure_averaged_fifo(n, &mut NoDelay, OutlierRejection::Disabled)
+                .unwrap_err(),
+            MeasureError::InvalidSampleCount
+        );
+    }
+    assert_eq!(lps22.destroy().conversions(), 0);
+
+    let samples = [Measurement::default(); MAX_SAMPLES + 1];
+    let result =
+        AveragedMeasurement::from_measurements(&samples[..MAX_SAMPLES], OutlierRejection::Disabled);
+    assert_eq!(result.unwrap().samples, MAX_SAMPLES);
+    assert!(AveragedMeasurement::from_measurements(&samples, OutlierRejection::Disabled).is_none());
+    assert!(AveragedMeasurement::from_measurements(&[], OutlierRejection::Disabled).is_none());
+}
@@ -0,0 +1,98 @@
+//! Fixtures shared by the integration tests
+#![allow(dead_code)]
+
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use embedded_hal::blocking::delay::DelayMs;
+use lps22hb::interface::Interface;
+use lps22hb::register::Registers;
+use lps22hb::sim::{SimError, SimulatedLps22hb};
+
+/// Delay that returns immediately
+pub struct NoDelay;
+
+impl DelayMs<u8> for NoDelay {
+    fn delay_ms(&mut self, _ms: u8) {}
+}
+
+/// Delay counting the milliseconds waited
+#[derive(Default)]
+pub struct CountingDelay(pub u32);
+
+impl DelayMs<u8> for CountingDelay {
+    fn delay_ms(&mut self, ms: u8) {
+        self.0 += ms as u32;
+    }
+}
+
+/// Simulator shared between the driver and a `SimClock`
+#[derive(Clone)]
+pub struct SharedSim(pub Rc<RefCell<SimulatedLps22hb>>);
+
+impl SharedSim {
+    pub fn new(sim: SimulatedLps22hb) -> Self {
+        SharedSim(Rc::new(RefCell::new(sim)))
+    }
+
+    /// Delay advancing the virtual time of the simulator
+    pub fn clock(&self) -> SimClock {
+        SimClock(self.0.clone())
+    }
+}
+
+impl Interface for SharedSim {
+    type Error = SimError;
+
+    fn write(&mut self, addr: u8, value: u8) -> Result<(), SimError> {
+        self.0.borrow_mut().write(addr, value)
+    }
+
+    fn write_multiple(&mut self, addr: u8, values: &[u8]) -> Result<(), SimError> {
+        self.0.borrow_mut().write_multiple(addr, values)
+    }
+
+    fn read(&mut self, addr: u8, buffer: &mut [u8]) -> Result<(), SimError> {
+        self.0.borrow_mut().read(addr, buffer)
+    }
+}
+
+/// Delay advancing the virtual time of a `SharedSim`
+pub struct SimClock(Rc<RefCell<SimulatedLps22hb>>);
+
+impl DelayMs<u8> for SimClock {
+    fn delay_ms(&mut self, ms: u8) {
+        self.0.borrow_mut().advance(ms as u32 * 1000);
+    }
+}
+
+/// Simulator ignoring the ONE_SHOT bit, so that one-shot conversions never complete
+pub struct StuckOneShot<I = SimulatedLps22hb>(pub I);
+
+impl<I: Interface<Error = SimError>> Interface for StuckOneShot<I> {
+    type Error = SimError;
+
+    fn write(&mut self, addr: u8, value: u8) -> Result<(), SimError> {
+        let value = if addr == Registers::CTRL_REG2.addr() {
+            value & !0b0000_0001
+        } else {
+            value
+        };
+        self.0.write(addr, value)
+    }
+
+    fn read(&mut self, addr: u8, buffer: &mut [u8]) -> Result<(), SimError> {
+        self.0.read(addr, buffer)
+    }
+}
+
+/// Deterministic noise roughly matching the sensor RMS noise (0.0075 hPa),
+/// added to the synthetic pressure profiles
+pub struct Noise(pub u32);
+
+impl Noise {
+    pub fn next(&mut self) -> f32 {
+        self.0 = self.0.wrapping_mul(1_664_525).wrapping_add(1_013_904_223);
+        ((self.0 >> 8) as f32 / (1u32 << 24) as f32 - 0.5) * 0.026
+    }
+}
@@ -0,0 +1,164 @@
+mod common;
+
+use common::{CountingDelay, NoDelay, StuckOneShot};
+use lps22hb::average::MeasureError;
+use lps22hb::dual::DualLps22hb;
+use lps22hb::interface::Interface;
+use lps22hb::register::Registers;
+use lps22hb::sim::{SimError, SimulatedLps22hb, Waveform};
+use lps22hb::*;
+
+/// Either a working simulator, one whose one-shots never complete
+/// or one that never reports new temperature data
+enum Bus {
+    Sim(SimulatedLps22hb),
+    Stuck(StuckOneShot),
+    NoTemperature(SimulatedLps22hb),
+}
+
+impl Interface for Bus {
+    type Error = SimError;
+
+    fn write(&mut self, addr: u8, value: u8) -> Result<(), SimError> {
+        match self {
+            Bus::Sim(sim) => sim.write(addr, value),
+            Bus::Stuck(sim) => sim.write(addr, value),
+            Bus::NoTemperature(sim) => sim.write(addr, value),
+        }
+    }
+
+    fn read(&mut self, addr: u8, buffer: &mut [u8]) -> Result<(), SimError> {
+        match self {
+            Bus::Sim(sim) => sim.read(addr, buffer),
+            Bus::Stuck(sim) => sim.read(addr, buffer),
+            Bus::NoTemperature(sim) => {
+                sim.read(addr, buffer)?;
+                if addr == Registers::STATUS.addr() {
+                    // clear T_DA
+                    buffer[0] &= !0b0000_0010;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+fn sim(pressure: f32) -> SimulatedLps22hb {
+    SimulatedLps22hb::with_waveforms(Waveform::Constant(pressure), Waveform::Constant(22.0))
+}
+
+fn dual(first: f32, second: f32) -> DualLps22hb<Bus> {
+    DualLps22hb::new(
+        LPS22HB::new(Bus::Sim(sim(first))),
+        LPS22HB::new(Bus::Sim(sim(second))),
+    )
+}
+
+fn conversions(bus: Bus) -> u32 {
+    match bus {
+        Bus::Sim(sim) => sim.conversions(),
+        Bus::Stuck(stuck) => stuck.0.conversions(),
+        Bus::NoTemperature(sim) => sim.conversions(),
+    }
+}
+
+#[test]
+fn one_shots_are_triggered_on_both_sensors() {
+    let mut dual = dual(1000.0, 1000.0);
+    dual.measure(&mut NoDelay).unwrap();
+    dual.measure(&mut NoDelay).unwrap();
+    let (first, second) = dual.destroy();
+    assert_eq!(conversions(first.destroy()), 2);
+    assert_eq!(conversions(second.destroy()), 2);
+}
+
+#[test]
+fn difference_is_first_minus_second() {
+    let mut dual = dual(1001.5, 1000.0);
+    let measurement = dual.measure(&mut NoDelay).unwrap();
+    assert_eq!(measurement.first.pressure, 1001.5);
+    assert_eq!(measurement.second.pressure, 1000.0);
+    assert_eq!(measurement.second.temperature, 22.0);
+    assert_eq!(measurement.difference(), 1.5);
+    assert_eq!(dual.read_difference().unwrap(), 1.5);
+}
+
+#[test]
+fn calibrated_offset_cancels_the_difference() {
+    let mut dual = dual(1000.0, 1000.25);
+    assert_eq!(dual.calibrate_offset(4, &mut NoDelay).unwrap(), 0.25);
+    assert_eq!(dual.measure(&mut NoDelay).unwrap().difference(), 0.0);
+    let (_, second) = dual.sensors();
+    assert_eq!(second.read_pressure_offset().unwrap(), 4);
+}
+
+#[test]
+fn measure_times_out_when_a_sensor_does_not_convert() {
+    let mut dual = DualLps22hb::new(
+        LPS22HB::new(Bus::Sim(sim(1000.0))),
+        LPS22HB::new(Bus::Stuck(StuckOneShot(sim(1000.0)))),
+    );
+    let mut delay = CountingDelay::default();
+    assert_eq!(dual.measure(&mut delay).unwrap_err(), MeasureError::Timeout);
+    assert_eq!(delay.0, 50);
+    assert_eq!(
+        dual.calibrate_offset(2, &mut NoDelay).unwrap_err(),
+        MeasureError::Timeout
+    );
+}
+
+#[test]
+fn measure_waits_for_the_temperature_too() {
+    let mut dual = DualLps22hb::new(
+        LPS22HB::new(Bus::Sim(sim(1000.0))),
+        LPS22HB::new(Bus::NoTemperature(sim(1000.0))),
+    );
+    assert_eq!(
+        dual.measure(&mut NoDelay).unwrap_err(),
+        MeasureError::Timeout
+    );
+}
+
+#[test]
+fn calibration_rejects_zero_samples() {
+    let mut dual = dual(1000.0, 1000.25);
+    assert_eq!(
+        dual.calibrate_offset(0, &mut NoDelay).unwrap_err(),
+        MeasureError::InvalidSampleCount
+    );
+}
+
+#[test]
+fn failed_calibration_restores_the_previous_offset() {
+    let mut dual = DualLps22hb::new(
+        LPS22HB::new(Bus::Sim(sim(1000.0))),
+        LPS22HB::new(Bus::Stuck(StuckOneShot(sim(1000.0)))),
+    );
+    let (_, second) = dual.sensors();
+    second.set_pressure_offset_raw(-7).unwrap();
+    assert_eq!(
+        dual.calibrate_offset(2, &mut NoDelay).unwrap_err(),
+        MeasureError::Timeout
+    );
+    let (_, second) = dual.sensors();
+    assert_eq!(second.read_pressure_offset().unwrap(), -7);
+}
+
+#[test]
+fn measure_does_not_pair_an_unread_sample() {
+    // the second sensor holds an unread sample from continuous mode and its one-shots never complete
+    let mut stale = sim(1000.0);
+    stale.write(Registers::CTRL_REG1.addr(), 0x50).unwrap();
+    stale.advance(20_000);
+    stale.write(Registers::CTRL_REG1.addr(), 0x00).unwrap();
+    assert_eq!(stale.conversions(), 1);
+
+    let mut dual = DualLps22hb::new(
+        LPS22HB::new(Bus::Sim(sim(1000.0))),
+        LPS22HB::new(Bus::Stuck(StuckOneShot(stale))),
+    );
+    assert_eq!(
+        dual.measure(&mut NoDelay).unwrap_err(),
+        MeasureError::Timeout
+    );
+}
@@ -0,0 +1,132 @@
+use lps22hb::interface::tracing::{TraceBuffer, Transaction};
+use lps22hb::interface::TracingInterface;
+use lps22hb::preset::Preset;
+use lps22hb::register::Registers;
+use lps22hb::sim::{SimulatedLps22hb, Waveform};
+use lps22hb::*;
+
+#[test]
+fn burst_reads_of_contiguous_registers() {
+    let trace = TracingInterface::new(SimulatedLps22hb::new(), TraceBuffer::<8>::new());
+    let mut lps22 = LPS22HB::new(trace);
+    let dump = lps22.dump_registers_destructive().unwrap();
+
+    let (_, buffer) = lps22.destroy().destroy();
+    let log: Vec<Transaction> = buffer.iter().copied().collect();
+    assert_eq!(
+        log,
+        [
+            Transaction::Read { addr: 0x0B, len: 3 },
+            Transaction::Read { addr: 0x0F, len: 4 },
+            Transaction::Read { addr: 0x14, len: 7 },
+            Transaction::Read { addr: 0x25, len: 8 },
+        ]
+    );
+
+    assert_eq!(dump.get(Registers::WHO_AM_I), Some(0xB1));
+    assert_eq!(dump.get(Registers::CTRL_REG2), Some(0x10));
+    assert_eq!(dump.get(Registers::LPFP_RES), None);
+    let registers: Vec<Registers> = dump.iter().map(|(register, _)| register).collect();
+    assert_eq!(registers.len(), 22);
+    assert_eq!(registers[0], Registers::INTERRUPT_CFG);
+    assert_eq!(registers[21], Registers::TEMP_OUT_H);
+}
+
+#[test]
+fn config_dump_has_no_side_effects() {
+    let mut sim = SimulatedLps22hb::new();
+    sim.set_register(Registers::INT_SOURCE, 0b0000_0101);
+    let mut lps22 = LPS22HB::new(sim);
+    lps22.one_shot().unwrap();
+    let trace = TracingInterface::new(lps22.destroy(), TraceBuffer::<8>::new());
+    let mut lps22 = LPS22HB::new(trace);
+    let dump = lps22.dump_config_registers().unwrap();
+
+    let (sim, buffer) = lps22.destroy().destroy();
+    let log: Vec<Transaction> = buffer.iter().copied().collect();
+    assert_eq!(
+        log,
+        [
+            Transaction::Read { addr: 0x0B, len: 3 },
+            Transaction::Read { addr: 0x0F, len: 4 },
+            Transaction::Read { addr: 0x14, len: 7 },
+            Transaction::Read { addr: 0x26, len: 2 },
+        ]
+    );
+    assert_eq!(dump.get(Registers::INT_SOURCE), None);
+    assert_eq!(dump.get(Registers::STATUS), Some(0b0000_0011));
+    assert_eq!(dump.pressure(), None);
+    assert_eq!(dump.iter().count(), 16);
+    let report = dump.to_string();
+    assert!(!report.contains("IA="));
+    assert!(report.ends_with("P_DA=1 T_DA=1 P_OR=0 T_OR=0"));
+
+    // the latched interrupt and the unread sample are still there
+    assert_eq!(sim.register(Registers::INT_SOURCE), 0b0000_0101);
+    assert_eq!(sim.register(Registers::STATUS), 0b0000_0011);
+}
+
+#[test]
+fn decoded_report() {
+    let mut lps22 = LPS22HB::new(SimulatedLps22hb::with_waveforms(
+        Waveform::Constant(1013.25),
+        Waveform::Constant(21.5),
+    ));
+    lps22.apply_preset(Preset::Drone).unwrap();
+    lps22.set_threshold(12).unwrap();
+    lps22.set_pressure_offset_raw(-40).unwrap();
+    lps22.set_reference_pressure(1000).unwrap();
+    lps22.one_shot().unwrap();
+    lps22.apply_preset(Preset::Drone).unwrap();
+
+    let dump = lps22.dump_registers_destructive().unwrap();
+    assert_eq!(dump.odr(), Ok(ODR::_75Hz));
+    assert_eq!(dump.fifo_mode(), Ok(FIFO_MODE::Stream));
+    assert_eq!(dump.int_drdy(), Ok(INT_DRDY::DataSignal));
+    assert_eq!(dump.lowpass_filter(), LowPassFilter::OdrDiv9);
+    assert_eq!(dump.power_mode(), PowerMode::LowNoise);
+    assert_eq!(dump.threshold(), 12.0);
+    assert_eq!(dump.pressure_offset(), -2.5);
+    assert_eq!(dump.reference_pressure(), 1000.0);
+    assert_eq!(dump.temperature(), Some(21.5));
+
+    let report = dump.to_string();
+    assert!(!report.contains('\n'));
+    assert!(report.starts_with(
+        "WHO_AM_I=0xb1 ODR=75Hz LPF=ODR/9 BDU=1 SIM=0 LC_EN=0 FIFO=Stream FIFO_EN=1 WTM=0 STOP_ON_FTH=0 IF_ADD_INC=1 I2C_DIS=0 INT_S=Data "
+    ));
+    assert!(report.contains("THS=12.0000hPa RPDS=-2.5000hPa REF=1000.0000hPa"));
+    assert!(report.ends_with("T=21.50C"));
+}
+
+#[test]
+fn flags_are_decoded() {
+    let mut lps22 = LPS22HB::new(SimulatedLps22hb::new());
+    let report = lps22.dump_registers_destructive().unwrap().to_string();
+    assert!(report.contains(" SIM=0 "));
+    assert!(report.contains(" IF_ADD_INC=1 I2C_DIS=0 "));
+    assert!(report.contains(" F_OVR=0 F_FTH=0 F_FSS5=0 "));
+    assert!(report.contains(" BOOT_STATUS=0 IA=0 PH=0 PL=0 FSS=0 FTH_FIFO=0 OVR=0 "));
+    assert!(report.contains(" P_DA=0 T_DA=0 P_OR=0 T_OR=0 "));
+
+    lps22.spi_config(SPI_Mode::_3wire).unwrap();
+    lps22.i2c_disable(true).unwrap();
+    lps22.address_incrementing(false).unwrap();
+    let report = lps22.dump_registers_destructive().unwrap().to_string();
+    assert!(report.contains(" SIM=1 "));
+    assert!(report.contains(" IF_ADD_INC=0 I2C_DIS=1 "));
+
+    // the status registers are read-only, set them in the simulator
+    let mut sim = SimulatedLps22hb::new();
+    sim.set_register(Registers::CTRL_REG3, 0b0011_1000);
+    sim.set_register(Registers::INT_SOURCE, 0b1000_0111);
+    sim.set_register(Registers::FIFO_STATUS, 0b1101_0100);
+    sim.set_register(Registers::STATUS, 0b0011_0011);
+    let report = LPS22HB::new(sim)
+        .dump_registers_destructive()
+        .unwrap()
+        .to_string();
+    assert!(report.contains(" F_OVR=1 F_FTH=1 F_FSS5=1 "));
+    assert!(report.contains(" BOOT_STATUS=1 IA=1 PH=1 PL=1 FSS=20 FTH_FIFO=1 OVR=1 "));
+    assert!(report.contains(" P_DA=1 T_DA=1 P_OR=1 T_OR=1 "));
+}
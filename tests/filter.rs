@@ -0,0 +1,89 @@
+use lps22hb::filter::*;
+use lps22hb::sensor::{Measurement, RawMeasurement};
+
+#[test]
+fn moving_average_float_and_fixed_point() {
+    let mut avg = MovingAverage::<f32, 4>::new();
+    assert_eq!(avg.update(1.0), 1.0);
+    assert_eq!(avg.update(3.0), 2.0);
+    avg.update(5.0);
+    avg.update(7.0);
+    // oldest sample (1.0) drops out
+    assert_eq!(avg.update(9.0), 6.0);
+
+    // raw pressure around 1013 hPa does not overflow the accumulator
+    let mut raw = MovingAverage::<i32, 32>::new();
+    let mut out = 0;
+    for i in 0..100 {
+        out = raw.update(4_149_248 + (i % 2));
+    }
+    assert_eq!(out, 4_149_248 + 1);
+}
+
+#[test]
+fn moving_average_float_sum_does_not_drift() {
+    let mut avg = MovingAverage::<f32, 4>::new();
+    // the small samples are lost in the rounding of the running sum while the large one is in it
+    avg.update(16_777_216.0);
+    let mut out = 0.0;
+    for _ in 0..7 {
+        out = avg.update(1.0);
+    }
+    assert_eq!(out, 1.0);
+}
+
+#[test]
+fn exponential_filter_converges() {
+    let mut iir = ExponentialFilter::new(0.5);
+    assert_eq!(Filter::<f32>::update(&mut iir, 0.0), 0.0);
+    assert_eq!(iir.update(10.0f32), 5.0);
+    assert_eq!(iir.update(10.0f32), 7.5);
+
+    let mut iir = ExponentialFilter::with_time_constant(1.0, 1.0);
+    assert_eq!(iir.update(100i16), 100);
+    assert_eq!(iir.update(0i16), 50);
+}
+
+#[test]
+fn median_rejects_spike() {
+    let mut median = MedianFilter::<i32, 5>::new();
+    for x in &[10, 11, 10, 12] {
+        median.update(*x);
+    }
+    assert_eq!(median.update(1000), 11);
+    assert_eq!(median.update(11), 11);
+
+    let mut median = MedianFilter::<f32, 4>::new();
+    median.update(1.0);
+    assert_eq!(median.update(2.0), 1.5);
+}
+
+#[test]
+fn kalman_reduces_noise() {
+    let mut kalman = Kalman1D::new(1e-5, 0.01);
+    let mut last = 0.0f32;
+    for i in 0..200 {
+        let noise = if i % 2 == 0 { 0.1 } else { -0.1 };
+        last = kalman.update(1000.0f32 + noise);
+    }
+    assert!((last - 1000.0).abs() < 0.02);
+    assert!(kalman.error_variance() < 0.01);
+
+    kalman.reset();
+    assert_eq!(kalman.update(5.0f32), 5.0);
+}
+
+#[test]
+fn paired_filters_measurements() {
+    let mut filter = Paired::new(MovingAverage::<f32, 2>::new(), MedianFilter::<f32, 3>::new());
+    filter.update(Measurement { pressure: 1000.0, temperature: 20.0 });
+    let out = filter.update(Measurement { pressure: 1002.0, temperature: 22.0 });
+    assert_eq!(out.pressure, 1001.0);
+    assert_eq!(out.temperature, 21.0);
+
+    let mut raw = Paired::new(MovingAverage::<i32, 2>::new(), ExponentialFilter::new(1.0));
+    raw.update(RawMeasurement { pressure: 4096, temperature: 2000 });
+    let out = raw.update(RawMeasurement { pressure: 8192, temperature: 2100 });
+    assert_eq!(out.pressure, 6144);
+    assert_eq!(out.temperature, 2100);
+}
@@ -0,0 +1,191 @@
+mod common;
+
+use std::ops::Range;
+
+use common::Noise;
+use lps22hb::altitude::{pressure_at_altitude, STANDARD_PRESSURE};
+use lps22hb::flight::*;
+use lps22hb::sensor::Measurement;
+use lps22hb::ODR;
+
+const RATE: f32 = 75.0;
+const PAD: f32 = 5.0;
+const BURN: f32 = 3.0;
+const THRUST: f32 = 120.0;
+const DESCENT: f32 = 30.0;
+
+/// Disturbance of the static pressure around Mach 1
+#[derive(Clone, Copy)]
+enum Glitch {
+    None,
+    /// +4 hPa between 330 and 345 m/s
+    Step,
+    /// Transonic signature of a static port: the reading falls by up to 3 hPa from Mach 0.9
+    /// (the altitude reads high), then jumps to +2 hPa across the shock at Mach 1,
+    /// fading out by Mach 1.1
+    Transonic,
+}
+
+/// Pressure error (hPa) of `glitch` at `speed` (m/s)
+fn glitch_error(glitch: Glitch, speed: f32) -> f32 {
+    let mach = speed / 340.0;
+    match glitch {
+        Glitch::None => 0.0,
+        Glitch::Step if speed > 330.0 && speed < 345.0 => 4.0,
+        Glitch::Step => 0.0,
+        Glitch::Transonic if mach > 0.9 && mach < 1.0 => -3.0 * (mach - 0.9) / 0.1,
+        Glitch::Transonic if (1.0..1.1).contains(&mach) => 2.0 * (1.1 - mach) / 0.1,
+        Glitch::Transonic => 0.0,
+    }
+}
+
+/// Synthetic flight profile at 75 Hz from a 300 m high launch site: 5 s on the pad, 3 s boost at 120 m/s²
+/// (burnout at 360 m/s), ballistic coast, descent under parachute at 30 m/s, 10 s on the ground.
+/// The rocket goes through Mach 1 at the end of the boost and again early in the coast.
+fn flight_profile(glitch: Glitch) -> Vec<Measurement> {
+    let mut noise = Noise(7);
+    let (mut altitude, mut speed, mut t) = (0.0f32, 0.0f32, 0.0f32);
+    let mut samples = Vec::new();
+    let mut landed_at = None;
+    while !matches!(landed_at, Some(landed) if t >= landed + 10.0) {
+        let dt = 1.0 / RATE;
+        let acceleration = if t < PAD {
+            0.0
+        } else if t < PAD + BURN {
+            THRUST
+        } else {
+            -9.81
+        };
+        speed += acceleration * dt;
+        if t > PAD + BURN && speed < -DESCENT {
+            speed = -DESCENT;
+        }
+        altitude += speed * dt;
+        if t > PAD && altitude <= 0.0 {
+            altitude = 0.0;
+            speed = 0.0;
+            landed_at.get_or_insert(t);
+        }
+        let pressure = pressure_at_altitude(300.0 + altitude, STANDARD_PRESSURE)
+            + noise.next()
+            + glitch_error(glitch, speed);
+        samples.push(Measurement {
+            pressure,
+            temperature: 20.0,
+        });
+        t += dt;
+    }
+    samples
+}
+
+fn replay_with(samples: &[Measurement], config: FlightConfig) -> Vec<FlightEvent> {
+    let mut detector = FlightDetector::new(ODR::_75Hz, config).unwrap();
+    let mut events = Vec::new();
+    // FIFO bursts of 32 samples
+    for burst in samples.chunks(32) {
+        detector.update_burst(burst, |event| events.push(event));
+    }
+    events
+}
+
+fn replay(samples: &[Measurement]) -> Vec<FlightEvent> {
+    let events = replay_with(samples, FlightConfig::default());
+    assert_eq!(
+        events.last().map(|e| e.kind),
+        Some(FlightEventKind::Landing)
+    );
+    events
+}
+
+/// Check the events against the profile, with the burnout altitude in `burnout_altitude`
+fn check_events(events: &[FlightEvent], burnout_altitude: Range<f32>) {
+    let kinds: Vec<FlightEventKind> = events.iter().map(|e| e.kind).collect();
+    assert_eq!(
+        kinds,
+        vec![
+            FlightEventKind::Launch,
+            FlightEventKind::Burnout,
+            FlightEventKind::Apogee,
+            FlightEventKind::Landing
+        ]
+    );
+    let apogee_time = PAD + BURN + THRUST * BURN / 9.81;
+    let apogee_altitude = THRUST * BURN * BURN / 2.0 + (THRUST * BURN).powi(2) / (2.0 * 9.81);
+
+    assert!((events[0].time - PAD).abs() < 0.2, "{:?}", events[0]);
+    assert!(
+        (events[1].time - (PAD + BURN)).abs() < 0.3,
+        "{:?}",
+        events[1]
+    );
+    assert!(
+        burnout_altitude.contains(&events[1].altitude),
+        "{:?}",
+        events[1]
+    );
+    assert!(
+        (events[2].time - apogee_time).abs() < 1.0,
+        "{:?}",
+        events[2]
+    );
+    assert!(
+        (events[2].altitude - apogee_altitude).abs() < 20.0,
+        "{:?}",
+        events[2]
+    );
+    let landing_time = apogee_time
+        + DESCENT / 9.81
+        + (apogee_altitude - DESCENT * DESCENT / (2.0 * 9.81)) / DESCENT;
+    assert!(
+        (events[3].time - landing_time).abs() < 5.0,
+        "{:?}",
+        events[3]
+    );
+    assert!(events[3].altitude.abs() < 5.0, "{:?}", events[3]);
+}
+
+/// The filtered speed peaks slightly after the true burnout, at 540 m
+const BURNOUT_ALTITUDE: Range<f32> = 530.0..650.0;
+
+#[test]
+fn detects_flight_events() {
+    check_events(&replay(&flight_profile(Glitch::None)), BURNOUT_ALTITUDE);
+}
+
+#[test]
+fn ignores_transonic_glitch() {
+    check_events(&replay(&flight_profile(Glitch::Step)), BURNOUT_ALTITUDE);
+    // the falling reading before the shock inflates the speed, which then peaks before burnout
+    check_events(&replay(&flight_profile(Glitch::Transonic)), 490.0..650.0);
+}
+
+#[test]
+fn transonic_signature_fakes_events_without_the_protections() {
+    let unprotected = FlightConfig {
+        mach_lockout_speed: f32::INFINITY,
+        max_residual: f32::INFINITY,
+        ..Default::default()
+    };
+    let events = replay_with(&flight_profile(Glitch::Transonic), unprotected);
+    let apogee_time = PAD + BURN + THRUST * BURN / 9.81;
+    assert!(
+        events.iter().any(|e| match e.kind {
+            FlightEventKind::Burnout => e.time < PAD + BURN - 0.3,
+            FlightEventKind::Apogee => e.time < apogee_time - 5.0,
+            _ => false,
+        }),
+        "{:?}",
+        events
+    );
+}
+
+#[test]
+fn stays_on_pad_with_noise() {
+    let mut detector = FlightDetector::new(ODR::_75Hz, FlightConfig::default()).unwrap();
+    let mut noise = Noise(3);
+    for _ in 0..(600.0 * RATE) as usize {
+        assert!(detector.update(980.0 + noise.next()).is_none());
+    }
+    assert_eq!(detector.phase(), FlightPhase::Pad);
+    assert!(detector.altitude().abs() < 1.0);
+}
@@ -0,0 +1,90 @@
+use lps22hb::altitude::pressure_at_altitude;
+use lps22hb::floor::*;
+use lps22hb::ODR;
+
+const RATE: f32 = 25.0;
+
+/// Pressure at 25 Hz for an altitude profile given as (duration s, vertical speed m/s) segments,
+/// with the weather adding `drift` hPa per hour
+fn trace(segments: &[(f32, f32)], drift: f32) -> Vec<f32> {
+    let mut altitude = 0.0;
+    let mut samples = Vec::new();
+    for &(duration, speed) in segments {
+        for _ in 0..(duration * RATE) as usize {
+            altitude += speed / RATE;
+            let hours = samples.len() as f32 / RATE / 3600.0;
+            samples.push(pressure_at_altitude(altitude, 1000.0 + drift * hours));
+        }
+    }
+    samples
+}
+
+fn events(samples: &[f32]) -> Vec<FloorEvent> {
+    let mut detector = FloorDetector::new(ODR::_25Hz, FloorDetectorConfig::default()).unwrap();
+    samples.iter().filter_map(|p| detector.update(*p)).collect()
+}
+
+#[test]
+fn stairs_up_one_floor() {
+    let samples = trace(&[(10.0, 0.0), (12.0, 0.25), (10.0, 0.0)], 0.0);
+    assert_eq!(
+        events(&samples),
+        vec![FloorEvent {
+            from: 0,
+            to: 1,
+            transport: Transport::Stairs
+        }]
+    );
+}
+
+#[test]
+fn elevator_down_three_floors() {
+    let samples = trace(&[(10.0, 0.0), (6.0, -1.5), (10.0, 0.0)], 0.0);
+    assert_eq!(
+        events(&samples),
+        vec![FloorEvent {
+            from: 0,
+            to: -3,
+            transport: Transport::Elevator
+        }]
+    );
+}
+
+#[test]
+fn landing_between_floors_is_not_a_change() {
+    let samples = trace(
+        &[
+            (10.0, 0.0),
+            (6.0, 0.25),
+            (10.0, 0.0),
+            (6.0, -0.25),
+            (10.0, 0.0),
+        ],
+        0.0,
+    );
+    assert!(events(&samples).is_empty());
+}
+
+#[test]
+fn tolerates_weather_drift() {
+    // 1.5 hPa/h (about 12 m/h) for four hours, then one floor up by stairs
+    let mut segments = vec![(4.0 * 3600.0, 0.0)];
+    segments.extend_from_slice(&[(12.0, 0.25), (10.0, 0.0)]);
+    let found = events(&trace(&segments, -1.5));
+    assert_eq!(found.len(), 1);
+    assert_eq!((found[0].from, found[0].to), (0, 1));
+}
+
+#[test]
+fn autozero_differential_input() {
+    let mut detector = FloorDetector::new(ODR::_25Hz, FloorDetectorConfig::default()).unwrap();
+    assert!(detector.update_differential(0.0).is_none());
+    detector.set_reference(1000.0);
+    let found: Vec<FloorEvent> = trace(&[(10.0, 0.0), (12.0, 0.25), (10.0, 0.0)], 0.0)
+        .iter()
+        .filter_map(|p| detector.update_differential(p - 1000.0))
+        .collect();
+    assert_eq!(found.len(), 1);
+    assert_eq!(detector.floor(), 1);
+    assert!((detector.relative_altitude() - 3.0).abs() < 0.5);
+}
@@ -0,0 +1,71 @@
+use lps22hb::leak::*;
+use lps22hb::sensor::Measurement;
+
+/// Daily temperature cycle between 10 and 40 °C, one sample per minute
+fn temperature(minute: usize) -> f32 {
+    25.0 + 15.0 * (minute as f32 / 1440.0 * std::f32::consts::TAU).sin()
+}
+
+#[test]
+fn sealed_enclosure_follows_temperature() {
+    let mut detector = LeakDetector::new(LeakDetectorConfig::default());
+    // sealed at 1013 hPa and 25 °C
+    let ratio = 1013.0 / (25.0 + 273.15);
+    for minute in 0..3 * 1440 {
+        let t = temperature(minute);
+        let status = detector.update(Measurement {
+            pressure: ratio * (t + 273.15),
+            temperature: t,
+        });
+        assert!(!matches!(status, LeakStatus::Leak(_)), "{:?}", status);
+    }
+    assert!((detector.learned_ratio().unwrap() - ratio).abs() < 1e-4);
+    assert!((detector.expected_pressure(25.0).unwrap() - 1013.0).abs() < 0.05);
+}
+
+#[test]
+fn leaking_enclosure_tracks_ambient() {
+    let mut detector = LeakDetector::new(LeakDetectorConfig::default());
+    let mut leak_at = None;
+    for minute in 0..1440 {
+        // sealed at first, open to the 1013 hPa ambient after two hours
+        let t = temperature(minute);
+        let pressure = if minute < 120 {
+            1013.0 / (25.0 + 273.15) * (t + 273.15)
+        } else {
+            1013.0
+        };
+        let status = detector.update(Measurement {
+            pressure,
+            temperature: t,
+        });
+        if let LeakStatus::Leak(deviation) = status {
+            leak_at.get_or_insert((minute, deviation));
+        }
+    }
+    // warmer than when sealed: the pressure is lower than expected
+    let (minute, deviation) = leak_at.expect("leak not detected");
+    assert!(minute > 120 && minute < 180, "{}", minute);
+    assert!(deviation < 0.0);
+}
+
+#[test]
+fn learning_and_relearning() {
+    let config = LeakDetectorConfig {
+        learning_samples: 3,
+        ..Default::default()
+    };
+    let sample = Measurement {
+        pressure: 1000.0,
+        temperature: 20.0,
+    };
+    let mut detector = LeakDetector::new(config);
+    assert_eq!(detector.update(sample), LeakStatus::Learning(1));
+    assert_eq!(detector.update(sample), LeakStatus::Learning(2));
+    assert_eq!(detector.update(sample), LeakStatus::Sealed(0.0));
+    detector.relearn();
+    assert!(detector.learned_ratio().is_none());
+
+    let detector = LeakDetector::with_ratio(config, 1000.0 / 293.15);
+    assert!((detector.expected_pressure(20.0).unwrap() - 1000.0).abs() < 1e-3);
+}
@@ -0,0 +1,53 @@
+use std::convert::TryFrom;
+
+use lps22hb::sim::SimulatedLps22hb;
+use lps22hb::*;
+
+#[test]
+fn rate_conversions() {
+    assert_eq!(ODR::_25Hz.hz(), Some(25.0));
+    assert_eq!(ODR::_10Hz.period(), Some(0.1));
+    assert_eq!(ODR::PowerDown.hz(), None);
+    assert_eq!(ODR::PowerDown.period(), None);
+
+    assert_eq!(ODR::nearest(0.0), ODR::PowerDown);
+    assert_eq!(ODR::nearest(f32::NAN), ODR::PowerDown);
+    assert_eq!(ODR::nearest(0.2), ODR::_1Hz);
+    assert_eq!(ODR::nearest(17.5), ODR::_10Hz);
+    assert_eq!(ODR::nearest(18.0), ODR::_25Hz);
+    assert_eq!(ODR::nearest(62.5), ODR::_50Hz);
+    assert_eq!(ODR::nearest(1000.0), ODR::_75Hz);
+}
+
+#[test]
+fn field_decoding() {
+    for odr in [
+        ODR::PowerDown,
+        ODR::_1Hz,
+        ODR::_10Hz,
+        ODR::_25Hz,
+        ODR::_50Hz,
+        ODR::_75Hz,
+    ] {
+        assert_eq!(ODR::try_from(odr.value() | 0x0E), Ok(odr));
+    }
+    assert_eq!(ODR::try_from(0x60), Err(InvalidFieldValue(0b110)));
+
+    assert_eq!(FIFO_MODE::try_from(0xDF), Ok(FIFO_MODE::Dynamic_Stream));
+    assert_eq!(
+        FIFO_MODE::try_from(FIFO_MODE::Stream_to_FIFO.value()),
+        Ok(FIFO_MODE::Stream_to_FIFO)
+    );
+    assert_eq!(FIFO_MODE::try_from(0xA0), Err(InvalidFieldValue(0b101)));
+
+    assert_eq!(INT_DRDY::try_from(0xC3), Ok(INT_DRDY::P_low_or_high));
+    assert_eq!(INT_DRDY::try_from(0x02), Ok(INT_DRDY::P_low));
+}
+
+#[test]
+fn datarate_read_back() {
+    let mut lps22 = LPS22HB::new(SimulatedLps22hb::new());
+    assert_eq!(lps22.get_datarate().unwrap(), Some(ODR::PowerDown));
+    lps22.set_datarate(ODR::nearest(40.0)).unwrap();
+    assert_eq!(lps22.get_datarate().unwrap(), Some(ODR::_50Hz));
+}
@@ -0,0 +1,103 @@
+use lps22hb::power::{ModelEstimate, PowerConfig};
+use lps22hb::preset::Preset;
+use lps22hb::sim::SimulatedLps22hb;
+use lps22hb::*;
+
+fn close(a: f32, b: f32) -> bool {
+    (a - b).abs() < 1e-4
+}
+
+#[test]
+fn datasheet_figures() {
+    // supply current at 1 Hz ODR
+    let config = PowerConfig {
+        odr: ODR::_1Hz,
+        ..Default::default()
+    };
+    assert_eq!(config.model_estimate().current, 12.0);
+    let config = PowerConfig {
+        power_mode: PowerMode::LowCurrent,
+        ..config
+    };
+    assert_eq!(config.model_estimate().current, 3.0);
+
+    // power-down current
+    assert_eq!(PowerConfig::default().model_estimate().current, 1.0);
+
+    // RMS noise with the ODR/20 filter, at another data rate than 1 Hz
+    let config = PowerConfig {
+        odr: ODR::_25Hz,
+        lowpass_filter: LowPassFilter::OdrDiv20,
+        ..Default::default()
+    };
+    assert_eq!(config.model_estimate().noise, 0.0075);
+    let config = PowerConfig {
+        power_mode: PowerMode::LowCurrent,
+        ..config
+    };
+    assert_eq!(config.model_estimate().noise, 0.015);
+}
+
+#[test]
+fn current_model_is_linear_in_the_rate() {
+    let config = PowerConfig {
+        odr: ODR::_25Hz,
+        lowpass_filter: LowPassFilter::OdrDiv20,
+        ..Default::default()
+    };
+    assert_eq!(
+        config.model_estimate(),
+        ModelEstimate {
+            current: 276.0,
+            noise: 0.0075
+        }
+    );
+
+    // power-down, with and without one-shot measurements
+    let config = PowerConfig {
+        power_mode: PowerMode::LowCurrent,
+        lowpass_filter: LowPassFilter::OdrDiv20,
+        ..Default::default()
+    };
+    assert_eq!(config.model_estimate().current, 1.0);
+    assert_eq!(config.model_estimate_one_shot(1.0).current, 3.0);
+    assert_eq!(config.model_estimate_one_shot(0.1).current, 1.2);
+}
+
+#[test]
+fn noise_grows_with_the_bandwidth() {
+    let noise = |power_mode, lowpass_filter| {
+        PowerConfig {
+            odr: ODR::_75Hz,
+            power_mode,
+            lowpass_filter,
+        }
+        .model_estimate()
+        .noise
+    };
+    let div20 = noise(PowerMode::LowNoise, LowPassFilter::OdrDiv20);
+    let div9 = noise(PowerMode::LowNoise, LowPassFilter::OdrDiv9);
+    let disabled = noise(PowerMode::LowNoise, LowPassFilter::Disabled);
+    assert!(div20 < div9 && div9 < disabled);
+    assert!(close(disabled, 0.0075 * 10f32.sqrt()));
+    assert!(noise(PowerMode::LowCurrent, LowPassFilter::OdrDiv20) > div20);
+}
+
+#[test]
+fn estimate_of_the_device_configuration() {
+    let mut lps22 = LPS22HB::new(SimulatedLps22hb::new());
+    lps22.apply_preset(Preset::IndoorNavigation).unwrap();
+    let config = lps22.get_power_config().unwrap().unwrap();
+    assert_eq!(
+        config,
+        PowerConfig {
+            odr: ODR::_25Hz,
+            power_mode: PowerMode::LowNoise,
+            lowpass_filter: LowPassFilter::OdrDiv20,
+        }
+    );
+    assert_eq!(
+        lps22.model_estimate_power().unwrap(),
+        Some(config.model_estimate())
+    );
+}
@@ -0,0 +1,32 @@
+use lps22hb::preset::Preset;
+use lps22hb::register::Registers;
+use lps22hb::sim::SimulatedLps22hb;
+use lps22hb::*;
+
+#[test]
+fn presets_are_read_back() {
+    let mut lps22 = LPS22HB::new(SimulatedLps22hb::new());
+    assert_eq!(lps22.get_preset().unwrap(), None);
+
+    for preset in [Preset::Drone, Preset::IndoorNavigation, Preset::WeatherMonitoring] {
+        lps22.apply_preset(preset).unwrap();
+        assert_eq!(lps22.get_preset().unwrap(), Some(preset));
+        assert_eq!(lps22.get_datarate().unwrap(), Some(preset.odr()));
+    }
+
+    // a single setting changed
+    lps22.set_power_mode(PowerMode::LowNoise).unwrap();
+    assert_eq!(lps22.get_preset().unwrap(), None);
+}
+
+#[test]
+fn drone_preset_registers() {
+    let mut lps22 = LPS22HB::new(SimulatedLps22hb::new());
+    lps22.apply_preset(Preset::Drone).unwrap();
+
+    let sim = lps22.destroy();
+    assert_eq!(sim.register(Registers::CTRL_REG1), 0x5A);
+    assert_eq!(sim.register(Registers::CTRL_REG2) & 0x60, 0x40);
+    assert_eq!(sim.register(Registers::FIFO_CTRL) & 0xE0, 0x40);
+    assert_eq!(sim.register(Registers::RES_CONF), 0x00);
+}
@@ -0,0 +1,73 @@
+use lps22hb::reduction::*;
+
+fn assert_close(actual: f32, expected: f32, tolerance: f32) {
+    assert!(
+        (actual - expected).abs() <= tolerance,
+        "{} differs from {} by more than {}",
+        actual,
+        expected,
+        tolerance
+    );
+}
+
+/// Standard atmosphere table: (elevation m, pressure hPa, temperature °C)
+const ISA: &[(f32, f32, f32)] = &[
+    (0.0, 1013.25, 15.0),
+    (500.0, 954.61, 11.75),
+    (1000.0, 898.76, 8.5),
+    (1500.0, 845.59, 5.25),
+    (2000.0, 794.98, 2.0),
+];
+
+#[test]
+fn qfe_is_station_pressure_at_datum() {
+    assert_close(Station::new(300.0).qfe(980.0), 980.0, 1e-3);
+    // sensor 10 m above the datum: about 1.2 hPa lower
+    let station = Station {
+        elevation: 300.0,
+        sensor_height: 10.0,
+    };
+    assert_close(station.qfe(980.0), 981.16, 0.02);
+}
+
+#[test]
+fn qnh_matches_standard_atmosphere() {
+    for &(elevation, pressure, _) in ISA {
+        assert_close(Station::new(elevation).qnh(pressure), 1013.25, 0.1);
+    }
+}
+
+#[test]
+fn qff_matches_standard_atmosphere_at_isa_temperature() {
+    for &(elevation, pressure, temperature) in ISA {
+        assert_close(
+            Station::new(elevation).qff(pressure, temperature),
+            1013.25,
+            0.2,
+        );
+    }
+}
+
+#[test]
+fn qff_depends_on_temperature() {
+    let station = Station::new(1000.0);
+    // mean column temperature -6.75 °C
+    assert_close(station.qff(900.0, -10.0), 1023.15, 0.1);
+    assert_close(
+        station.qff_with_mean_temperature(900.0, -6.75),
+        1023.15,
+        0.1,
+    );
+    // colder air column gives a higher QFF than QNH, warmer a lower one
+    assert!(station.qff(900.0, -10.0) > station.qnh(900.0));
+    assert!(station.qff(900.0, 30.0) < station.qnh(900.0));
+}
+
+#[test]
+fn reduction_is_tagged() {
+    let station = Station::new(1000.0);
+    let qnh = station.reduce(Reduction::Qnh, 898.76, 8.5);
+    assert_eq!(qnh.reduction, Reduction::Qnh);
+    assert_close(qnh.pressure, 1013.25, 0.1);
+    assert_eq!(station.reduce(Reduction::Qfe, 898.76, 8.5).pressure, 898.76);
+}
@@ -0,0 +1,139 @@
+use lps22hb::fifo::FIFOConfig;
+use lps22hb::interface::replay::ReplayError;
+use lps22hb::interface::tracing::Transaction;
+use lps22hb::interface::{Interface, RecordingInterface, ReplayInterface};
+use lps22hb::sensor::Measurement;
+use lps22hb::sim::{SimulatedLps22hb, Waveform};
+use lps22hb::*;
+
+fn fifo_drain<T: Interface>(lps22: &mut LPS22HB<T>, samples: &mut [Measurement]) -> usize
+where
+    T::Error: core::fmt::Debug,
+{
+    let level = lps22.get_fifo_status().unwrap().fifo_level;
+    assert_eq!(level, 32);
+    lps22.read_fifo(samples).unwrap()
+}
+
+/// Record a FIFO drain of the simulator
+fn record() -> (Vec<u8>, [Measurement; 32]) {
+    let sim = SimulatedLps22hb::with_waveforms(
+        Waveform::Ramp {
+            start: 990.0,
+            step: 0.25,
+        },
+        Waveform::Constant(20.0),
+    );
+    let mut lps22 = LPS22HB::new(RecordingInterface::<_, 1024>::new(sim));
+    lps22
+        .configure_fifo(
+            FIFO_ON::Enabled,
+            FIFOConfig {
+                fifo_mode: FIFO_MODE::FIFO,
+                ..Default::default()
+            },
+        )
+        .unwrap();
+    lps22.set_datarate(ODR::_75Hz).unwrap();
+    let mut recording = lps22.destroy();
+    recording.interface_mut().advance(1_000_000);
+    let mut lps22 = LPS22HB::new(recording);
+
+    let mut samples = [Measurement::default(); 32];
+    assert_eq!(fifo_drain(&mut lps22, &mut samples), 32);
+    let recording = lps22.destroy();
+    assert!(!recording.is_truncated());
+    (recording.as_bytes().to_vec(), samples)
+}
+
+#[test]
+fn replay_of_a_fifo_drain() {
+    let (bytes, recorded) = record();
+    // 3 writes, 2 single byte reads of the control registers, 3 of FIFO_STATUS and 32 samples
+    assert_eq!(bytes.len(), 3 * 3 + 5 * 4 + 32 * 8);
+    assert_eq!(&bytes[..4], b"R\x11\x01\x10");
+
+    let mut lps22 = LPS22HB::new(ReplayInterface::new(&bytes));
+    lps22
+        .configure_fifo(
+            FIFO_ON::Enabled,
+            FIFOConfig {
+                fifo_mode: FIFO_MODE::FIFO,
+                ..Default::default()
+            },
+        )
+        .unwrap();
+    lps22.set_datarate(ODR::_75Hz).unwrap();
+    let mut samples = [Measurement::default(); 32];
+    assert_eq!(fifo_drain(&mut lps22, &mut samples), 32);
+    for (replayed, recorded) in samples.iter().zip(recorded.iter()) {
+        assert_eq!(replayed.pressure, recorded.pressure);
+        assert_eq!(replayed.temperature, 20.0);
+    }
+    assert_eq!(samples[31].pressure, 990.0 + 31.0 * 0.25);
+    assert!(lps22.destroy().is_finished());
+}
+
+#[test]
+fn replay_detects_a_different_sequence() {
+    let (bytes, _) = record();
+    let mut lps22 = LPS22HB::new(ReplayInterface::new(&bytes));
+    // FIFO disabled instead of enabled
+    let err = lps22
+        .configure_fifo(FIFO_ON::Disabled, FIFOConfig::default())
+        .unwrap_err();
+    assert_eq!(
+        err,
+        ReplayError::Mismatch {
+            offset: 4,
+            expected: Transaction::Write {
+                addr: 0x11,
+                value: 0x50
+            },
+            actual: Transaction::Write {
+                addr: 0x11,
+                value: 0x10
+            },
+        }
+    );
+
+    let mut replay = ReplayInterface::new(b"W\x10\x50R\x28");
+    replay.write(0x10, 0x50).unwrap();
+    assert_eq!(replay.read(0x28, &mut [0; 3]), Err(ReplayError::Corrupt(3)));
+    let mut replay = ReplayInterface::new(&[]);
+    assert_eq!(
+        replay.read(0x0F, &mut [0]),
+        Err(ReplayError::EndOfRecording(Transaction::Read {
+            addr: 0x0F,
+            len: 1
+        }))
+    );
+}
+
+#[test]
+fn recording_stops_when_full() {
+    let mut recording = RecordingInterface::<_, 6>::new(SimulatedLps22hb::new());
+    recording.write(0x0C, 1).unwrap();
+    recording.read(0x0F, &mut [0]).unwrap();
+    assert_eq!(recording.as_bytes(), b"W\x0c\x01");
+    assert!(recording.is_truncated());
+    // nothing recorded after the first transaction left out
+    recording.write(0x0C, 2).unwrap();
+    assert_eq!(recording.as_bytes().len(), 3);
+}
+
+#[test]
+fn replay_of_multiple_byte_writes() {
+    let mut lps22 = LPS22HB::new(RecordingInterface::<_, 16>::new(SimulatedLps22hb::new()));
+    lps22.set_reference_pressure(990).unwrap();
+    let recording = lps22.destroy();
+    assert_eq!(recording.as_bytes(), b"M\x15\x03\x00\xe0\x3d");
+
+    let mut lps22 = LPS22HB::new(ReplayInterface::new(recording.as_bytes()));
+    assert_eq!(
+        lps22.set_reference_pressure(991),
+        Err(ReplayError::DataMismatch(0))
+    );
+    lps22.set_reference_pressure(990).unwrap();
+    assert!(lps22.destroy().is_finished());
+}
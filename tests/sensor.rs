@@ -0,0 +1,78 @@
+use lps22hb::interface::Interface;
+use lps22hb::*;
+
+/// Register file answering reads with auto-increment
+struct Registers([u8; 0x80]);
+
+impl Interface for Registers {
+    type Error = ();
+
+    fn write(&mut self, addr: u8, value: u8) -> Result<(), ()> {
+        self.0[addr as usize] = value;
+        Ok(())
+    }
+
+    fn read(&mut self, addr: u8, buffer: &mut [u8]) -> Result<(), ()> {
+        let start = addr as usize;
+        buffer.copy_from_slice(&self.0[start..start + buffer.len()]);
+        Ok(())
+    }
+}
+
+fn sensor_with(addr: u8, bytes: &[u8]) -> LPS22HB<Registers> {
+    let mut registers = Registers([0; 0x80]);
+    let start = addr as usize;
+    registers.0[start..start + bytes.len()].copy_from_slice(bytes);
+    LPS22HB::new(registers)
+}
+
+// -1 hPa = -4096 LSB = 0xFFF000
+const MINUS_ONE_HPA: [u8; 3] = [0x00, 0xF0, 0xFF];
+
+#[test]
+fn negative_autozero_pressure_is_sign_extended() {
+    let mut sensor = sensor_with(0x28, &MINUS_ONE_HPA);
+    assert_eq!(sensor.read_pressure().unwrap(), -1.0);
+}
+
+#[test]
+fn negative_pressure_in_measurement_is_sign_extended() {
+    let mut sensor = sensor_with(0x28, &[0x00, 0xF0, 0xFF, 0xC4, 0x09]);
+    let measurement = sensor.read_measurement().unwrap();
+    assert_eq!(measurement.pressure, -1.0);
+    assert_eq!(measurement.temperature, 25.0);
+    assert_eq!(sensor.read_measurement_raw().unwrap().pressure, -4096);
+}
+
+#[test]
+fn negative_reference_pressure_is_sign_extended() {
+    let mut sensor = sensor_with(0x15, &MINUS_ONE_HPA);
+    assert_eq!(sensor.read_reference_pressure().unwrap(), -1.0);
+}
+
+#[test]
+fn positive_pressure_is_unchanged() {
+    // 1013.25 hPa = 4150272 LSB = 0x3F5400
+    let mut sensor = sensor_with(0x28, &[0x00, 0x54, 0x3F]);
+    assert_eq!(sensor.read_pressure().unwrap(), 1013.25);
+}
+
+#[test]
+fn out_of_range_settings_saturate() {
+    let mut sensor = sensor_with(0x00, &[]);
+    sensor.set_reference_pressure(2047).unwrap();
+    assert_eq!(sensor.read_reference_pressure().unwrap(), 2047.0);
+    sensor.set_reference_pressure(2048).unwrap();
+    assert_eq!(sensor.destroy().0[0x15..0x18], [0xFF, 0xFF, 0x7F]);
+
+    let mut sensor = sensor_with(0x00, &[]);
+    sensor.set_pressure_offset(2047).unwrap();
+    assert_eq!(sensor.read_pressure_offset().unwrap(), 2047 * 16);
+    sensor.set_pressure_offset(u16::MAX).unwrap();
+    assert_eq!(sensor.read_pressure_offset().unwrap(), i16::MAX);
+
+    sensor.set_threshold(4095).unwrap();
+    assert_eq!(sensor.read_threshold().unwrap() as u16, 4095 * 16);
+    sensor.set_threshold(4096).unwrap();
+    assert_eq!(sensor.read_threshold().unwrap() as u16, u16::MAX);
+}
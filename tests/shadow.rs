@@ -0,0 +1,126 @@
+use std::cell::RefCell;
+
+use lps22hb::fifo::FIFOConfig;
+use lps22hb::interface::tracing::Transaction;
+use lps22hb::interface::TracingInterface;
+use lps22hb::register::Registers;
+use lps22hb::sim::SimulatedLps22hb;
+use lps22hb::*;
+
+/// Number of reads and writes in the log, emptying it
+fn count(log: &RefCell<Vec<Transaction>>) -> (usize, usize) {
+    let mut log = log.borrow_mut();
+    let reads = log
+        .iter()
+        .filter(|t| matches!(t, Transaction::Read { .. }))
+        .count();
+    let writes = log.len() - reads;
+    log.clear();
+    (reads, writes)
+}
+
+#[test]
+fn configuration_without_read_back() {
+    let log = RefCell::new(Vec::new());
+    let trace = TracingInterface::new(SimulatedLps22hb::new(), |t| log.borrow_mut().push(t));
+    let mut lps22 = LPS22HB::new(trace);
+
+    lps22.shadow_enable(true);
+    // one burst read per contiguous block of registers
+    lps22.sync().unwrap();
+    assert_eq!(count(&log), (4, 0));
+
+    lps22.set_datarate(ODR::_25Hz).unwrap();
+    lps22.lowpass_filter(true, true).unwrap();
+    lps22
+        .configure_fifo(
+            FIFO_ON::Enabled,
+            FIFOConfig {
+                fifo_mode: FIFO_MODE::Stream,
+                ..Default::default()
+            },
+        )
+        .unwrap();
+    assert_eq!(count(&log), (0, 4));
+
+    // status registers are always read from the device
+    lps22.get_data_status().unwrap();
+    assert_eq!(count(&log), (1, 0));
+
+    let (sim, _) = lps22.destroy().destroy();
+    assert_eq!(sim.register(Registers::CTRL_REG1), 0x3C);
+    assert_eq!(sim.register(Registers::CTRL_REG2), 0x50);
+    assert_eq!(sim.register(Registers::FIFO_CTRL), 0x40);
+}
+
+#[test]
+fn self_clearing_bits_and_reset() {
+    let log = RefCell::new(Vec::new());
+    let trace = TracingInterface::new(SimulatedLps22hb::new(), |t| log.borrow_mut().push(t));
+    let mut lps22 = LPS22HB::new(trace);
+    lps22.shadow_enable(true);
+
+    // filled on first use
+    lps22.bdu_enable(true).unwrap();
+    assert_eq!(count(&log), (1, 1));
+    // the output registers are read to clear the data available flags, CTRL_REG2 comes from the shadow
+    lps22.one_shot().unwrap();
+    lps22.one_shot().unwrap();
+    assert_eq!(count(&log), (3, 4));
+
+    // the device is back to its defaults, the shadow copy is dropped
+    lps22.software_reset().unwrap();
+    count(&log);
+    lps22.bdu_enable(true).unwrap();
+    assert_eq!(count(&log), (1, 1));
+
+    // RESET_AZ clears AUTOZERO, which must not be written back
+    lps22.autozero_config(true).unwrap();
+    lps22.autozero_reset().unwrap();
+    lps22.autorifp_config(true).unwrap();
+    assert_eq!(count(&log), (1, 3));
+
+    let (sim, _) = lps22.destroy().destroy();
+    assert_eq!(sim.register(Registers::CTRL_REG1), 0x02);
+    assert_eq!(sim.register(Registers::INTERRUPT_CFG), 0x80);
+}
+
+#[test]
+fn disabled_by_default() {
+    let log = RefCell::new(Vec::new());
+    let trace = TracingInterface::new(SimulatedLps22hb::new(), |t| log.borrow_mut().push(t));
+    let mut lps22 = LPS22HB::new(trace);
+    lps22.bdu_enable(true).unwrap();
+    lps22.bdu_enable(false).unwrap();
+    assert_eq!(count(&log), (2, 2));
+
+    lps22.shadow_enable(true);
+    lps22.bdu_enable(true).unwrap();
+    lps22.invalidate_shadow();
+    lps22.bdu_enable(false).unwrap();
+    assert_eq!(count(&log), (2, 2));
+}
+
+#[test]
+fn multi_field_updates_use_one_read_modify_write() {
+    let log = RefCell::new(Vec::new());
+    let trace = TracingInterface::new(SimulatedLps22hb::new(), |t| log.borrow_mut().push(t));
+    let mut lps22 = LPS22HB::new(trace);
+
+    lps22.lowpass_filter(true, true).unwrap();
+    assert_eq!(count(&log), (1, 1));
+    lps22
+        .configure_fifo(
+            FIFO_ON::Enabled,
+            FIFOConfig {
+                enable_watermark: FLAG::Enabled,
+                ..Default::default()
+            },
+        )
+        .unwrap();
+    assert_eq!(count(&log), (1, 2));
+
+    let (sim, _) = lps22.destroy().destroy();
+    assert_eq!(sim.register(Registers::CTRL_REG1), 0x0C);
+    assert_eq!(sim.register(Registers::CTRL_REG2), 0x70);
+}
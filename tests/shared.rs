@@ -0,0 +1,141 @@
+use std::cell::RefCell;
+
+use embedded_hal::blocking::i2c::{Write, WriteRead};
+use lps22hb::interface::i2c::I2cAddress;
+use lps22hb::interface::{I2cInterface, Interface, SharedI2c};
+use lps22hb::register::Registers;
+use lps22hb::sim::{SimError, SimulatedLps22hb, Waveform};
+use lps22hb::typestate::PowerDown;
+use lps22hb::*;
+
+const LPS22HB_ADDR: u8 = 0b1011100;
+const OTHER_ADDR: u8 = 0x5F;
+
+/// I2C bus with the simulated LPS22HB (SA0 tied to ground) and another device
+struct Bus {
+    lps22hb: SimulatedLps22hb,
+    other: Vec<u8>,
+    /// Number of reads from the LPS22HB
+    reads: usize,
+}
+
+impl Bus {
+    fn new() -> Self {
+        Bus {
+            lps22hb: SimulatedLps22hb::with_waveforms(
+                Waveform::Constant(1001.5),
+                Waveform::Constant(22.0),
+            ),
+            other: Vec::new(),
+            reads: 0,
+        }
+    }
+}
+
+impl Write for Bus {
+    type Error = SimError;
+
+    fn write(&mut self, address: u8, bytes: &[u8]) -> Result<(), SimError> {
+        match address {
+            LPS22HB_ADDR => self.lps22hb.write_multiple(bytes[0], &bytes[1..]),
+            _ => {
+                self.other.extend_from_slice(bytes);
+                Ok(())
+            }
+        }
+    }
+}
+
+impl WriteRead for Bus {
+    type Error = SimError;
+
+    fn write_read(&mut self, address: u8, bytes: &[u8], buffer: &mut [u8]) -> Result<(), SimError> {
+        assert_eq!(address, LPS22HB_ADDR);
+        self.reads += 1;
+        self.lps22hb.read(bytes[0], buffer)
+    }
+}
+
+/// A driver for the other device, taking the bus by value
+struct OtherDriver<I2C> {
+    i2c: I2C,
+}
+
+impl<I2C: Write> OtherDriver<I2C> {
+    fn poke(&mut self, value: u8) {
+        self.i2c.write(OTHER_ADDR, &[0x20, value]).ok();
+    }
+}
+
+#[test]
+fn refcell_shared_bus() {
+    let bus = RefCell::new(Bus::new());
+    let mut lps22 = LPS22HB::new(I2cInterface::init(
+        SharedI2c::new(&bus),
+        I2cAddress::SA0_GND,
+    ));
+    let mut other = OtherDriver {
+        i2c: SharedI2c::new(&bus),
+    };
+
+    lps22.one_shot().unwrap();
+    other.poke(1);
+    lps22.set_reference_pressure(1000).unwrap();
+    other.poke(2);
+    assert_eq!(lps22.read_pressure().unwrap(), 1001.5);
+    assert_eq!(lps22.read_reference_pressure().unwrap(), 1000.0);
+
+    assert_eq!(bus.borrow().other, [0x20, 1, 0x20, 2]);
+    assert_eq!(bus.borrow().lps22hb.conversions(), 1);
+}
+
+#[test]
+fn borrowed_bus_per_call() {
+    let mut bus = Bus::new();
+
+    LPS22HB::new(I2cInterface::borrowed(&mut bus, I2cAddress::SA0_GND))
+        .one_shot()
+        .unwrap();
+    // the bus is free again between calls
+    bus.write(OTHER_ADDR, &[0x20, 3]).unwrap();
+    let temperature = LPS22HB::new(I2cInterface::borrowed(&mut bus, I2cAddress::SA0_GND))
+        .read_temperature()
+        .unwrap();
+
+    assert_eq!(temperature, 22.0);
+    assert_eq!(bus.other, [0x20, 3]);
+}
+
+#[test]
+fn detached_driver_keeps_its_state_between_borrows() {
+    let mut bus = Bus::new();
+    let mut lps22 = LPS22HB::detached(I2cAddress::SA0_GND);
+    lps22
+        .with_bus(&mut bus, |lps22| {
+            lps22.shadow_enable(true);
+            lps22.sync()
+        })
+        .unwrap();
+    bus.write(OTHER_ADDR, &[0x20, 4]).unwrap();
+
+    // the shadow copy filled by the first borrow serves the read-modify-write
+    let reads = bus.reads;
+    lps22
+        .with_bus(&mut bus, |lps22| lps22.bdu_enable(true))
+        .unwrap();
+    assert_eq!(bus.reads, reads);
+    assert_eq!(bus.lps22hb.register(Registers::CTRL_REG1), 0x02);
+
+    // the typestate mode carries over too
+    let lps22: LPS22HB<_, PowerDown> = lps22.attach(&mut bus).into_power_down().unwrap().detach();
+    bus.write(OTHER_ADDR, &[0x20, 5]).unwrap();
+    let lps22 = lps22.attach(&mut bus).start_one_shot().unwrap().detach();
+    bus.write(OTHER_ADDR, &[0x20, 6]).unwrap();
+    let mut lps22 = lps22.attach(&mut bus);
+    assert!(lps22.is_ready().unwrap());
+    let (lps22, measurement) = lps22.finish().unwrap();
+    let _: LPS22HB<_, PowerDown> = lps22.detach();
+
+    assert_eq!(measurement.pressure, 1001.5);
+    assert_eq!(bus.other, [0x20, 4, 0x20, 5, 0x20, 6]);
+}
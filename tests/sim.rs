@@ -0,0 +1,281 @@
+use embedded_hal::blocking::delay::DelayMs;
+use lps22hb::average::OutlierRejection;
+use lps22hb::interface::Interface;
+use lps22hb::interrupt::InterruptConfig;
+use lps22hb::register::Registers;
+use lps22hb::sim::{SimError, SimulatedLps22hb, Waveform};
+use lps22hb::storm::{StormAlarm, StormAlarmConfig, StormEvent};
+use lps22hb::*;
+
+struct NoDelay;
+
+impl DelayMs<u8> for NoDelay {
+    fn delay_ms(&mut self, _ms: u8) {}
+}
+
+fn sensor(pressure: Waveform) -> LPS22HB<SimulatedLps22hb> {
+    LPS22HB::new(SimulatedLps22hb::with_waveforms(
+        pressure,
+        Waveform::Constant(25.0),
+    ))
+}
+
+#[test]
+fn power_on_defaults() {
+    let mut lps22 = sensor(Waveform::Constant(1000.0));
+    assert_eq!(lps22.get_device_id().unwrap(), 0xB1);
+    let sim = lps22.destroy();
+    assert_eq!(sim.register(Registers::CTRL_REG2), 0x10);
+    assert_eq!(sim.register(Registers::CTRL_REG1), 0x00);
+}
+
+#[test]
+fn read_only_and_reserved_registers() {
+    let mut sim = SimulatedLps22hb::new();
+    assert_eq!(
+        sim.write(0x0F, 0),
+        Err(SimError::ReadOnly(Registers::WHO_AM_I))
+    );
+    assert_eq!(sim.write(0x0E, 0), Err(SimError::InvalidAddress(0x0E)));
+    assert_eq!(
+        sim.read(0x00, &mut [0]),
+        Err(SimError::InvalidAddress(0x00))
+    );
+}
+
+#[test]
+fn one_shot_conversion_and_status() {
+    let mut lps22 = sensor(Waveform::Ramp {
+        start: 1000.0,
+        step: 0.5,
+    });
+    assert!(!lps22.get_data_status().unwrap().press_available);
+
+    lps22.one_shot().unwrap();
+    let status = lps22.get_data_status().unwrap();
+    assert!(status.press_available && status.temp_available);
+    assert_eq!(lps22.read_pressure().unwrap(), 1000.0);
+    assert_eq!(lps22.read_temperature().unwrap(), 25.0);
+    assert!(!lps22.get_data_status().unwrap().press_available);
+
+    // two conversions without reading: overrun
+    // (triggered on the bus, `one_shot` reads the unread sample first)
+    let mut sim = lps22.destroy();
+    sim.write(Registers::CTRL_REG2.addr(), 0x11).unwrap();
+    sim.write(Registers::CTRL_REG2.addr(), 0x11).unwrap();
+    let mut lps22 = LPS22HB::new(sim);
+    let status = lps22.get_data_status().unwrap();
+    assert!(status.press_overrun && status.temp_overrun);
+    assert_eq!(lps22.read_measurement().unwrap().pressure, 1001.0);
+
+    // ONE_SHOT is self-clearing
+    let sim = lps22.destroy();
+    assert_eq!(sim.register(Registers::CTRL_REG2), 0x10);
+    assert_eq!(sim.conversions(), 3);
+}
+
+#[test]
+fn address_auto_increment() {
+    let mut lps22 = sensor(Waveform::Constant(1013.25));
+    lps22.one_shot().unwrap();
+    lps22.address_incrementing(false).unwrap();
+    let mut sim = lps22.destroy();
+    let mut data = [0u8; 3];
+    sim.read(Registers::PRESS_OUT_XL.addr(), &mut data).unwrap();
+    assert_eq!(data[0], data[1]);
+    assert_eq!(data[1], data[2]);
+}
+
+#[test]
+fn software_reset_restores_defaults() {
+    let mut lps22 = sensor(Waveform::Constant(1000.0));
+    lps22.set_datarate(ODR::_10Hz).unwrap();
+    lps22.bdu_enable(true).unwrap();
+    lps22.software_reset().unwrap();
+    lps22.reboot().unwrap();
+    let sim = lps22.destroy();
+    assert_eq!(sim.register(Registers::CTRL_REG1), 0x00);
+    assert_eq!(sim.register(Registers::CTRL_REG2), 0x10);
+}
+
+#[test]
+fn pressure_offset_and_autozero() {
+    let mut lps22 = sensor(Waveform::Ramp {
+        start: 1000.0,
+        step: -0.25,
+    });
+    lps22.set_pressure_offset_raw(-16).unwrap();
+    lps22.one_shot().unwrap();
+    assert_eq!(lps22.read_pressure().unwrap(), 1001.0);
+
+    lps22.autozero_config(true).unwrap();
+    lps22.one_shot().unwrap();
+    assert_eq!(lps22.read_reference_pressure().unwrap(), 1000.75);
+    assert_eq!(lps22.read_pressure().unwrap(), 0.0);
+    lps22.one_shot().unwrap();
+    assert_eq!(lps22.read_pressure().unwrap(), -0.25);
+
+    // RESET_AZ is self-clearing and disables AUTOZERO
+    lps22.autozero_reset().unwrap();
+    lps22.one_shot().unwrap();
+    assert_eq!(lps22.read_pressure().unwrap(), 1000.25);
+    let sim = lps22.destroy();
+    assert_eq!(sim.register(Registers::INTERRUPT_CFG), 0x00);
+}
+
+#[test]
+fn reference_pressure_for_autorifp() {
+    let mut lps22 = sensor(Waveform::Constant(1003.0));
+    lps22.set_threshold(2).unwrap();
+    lps22
+        .configure_interrupts(InterruptConfig {
+            enable_high_event: FLAG::Enabled,
+            enable_differential: FLAG::Enabled,
+            ..Default::default()
+        })
+        .unwrap();
+    lps22.autorifp_config(true).unwrap();
+    lps22.one_shot().unwrap();
+    assert_eq!(lps22.read_reference_pressure().unwrap(), 1003.0);
+    assert!(!lps22.get_int_status().unwrap().diff_press_high);
+
+    // a lower reference makes the same pressure a high pressure event
+    lps22.set_reference_pressure(1000).unwrap();
+    assert_eq!(lps22.read_reference_pressure().unwrap(), 1000.0);
+    lps22.one_shot().unwrap();
+    assert!(lps22.get_int_status().unwrap().diff_press_high);
+
+    // without address auto-increment both bytes are written and read one at a time
+    lps22.address_incrementing(false).unwrap();
+    lps22.set_threshold(20).unwrap();
+    assert_eq!(lps22.read_threshold().unwrap(), 20 * 16);
+    lps22.set_reference_pressure(1001).unwrap();
+    assert_eq!(lps22.read_reference_pressure().unwrap(), 1001.0);
+    let sim = lps22.destroy();
+    assert_eq!(sim.register(Registers::THS_P_L), 0x40);
+    assert_eq!(sim.register(Registers::THS_P_H), 0x01);
+}
+
+#[test]
+fn power_mode_switch_keeps_the_odr() {
+    let mut lps22 = sensor(Waveform::Constant(1000.0));
+    assert_eq!(lps22.get_power_mode().unwrap(), PowerMode::LowNoise);
+
+    lps22.set_datarate(ODR::_25Hz).unwrap();
+    lps22.lowpass_filter(true, false).unwrap();
+    lps22.set_power_mode(PowerMode::LowCurrent).unwrap();
+    assert_eq!(lps22.get_power_mode().unwrap(), PowerMode::LowCurrent);
+    lps22.set_power_mode(PowerMode::LowNoise).unwrap();
+    assert_eq!(lps22.get_power_mode().unwrap(), PowerMode::LowNoise);
+
+    let sim = lps22.destroy();
+    assert_eq!(sim.register(Registers::CTRL_REG1), 0x38);
+    assert_eq!(sim.register(Registers::RES_CONF), 0x00);
+}
+
+#[test]
+fn lowpass_filter_setting_and_cutoff() {
+    let mut lps22 = sensor(Waveform::Constant(1000.0));
+    assert_eq!(lps22.get_lowpass_filter().unwrap(), LowPassFilter::Disabled);
+    assert_eq!(lps22.get_lowpass_cutoff().unwrap(), None);
+
+    lps22.set_datarate(ODR::_50Hz).unwrap();
+    assert_eq!(lps22.get_lowpass_cutoff().unwrap(), Some(25.0));
+    lps22.set_lowpass_filter(LowPassFilter::OdrDiv20, true).unwrap();
+    assert_eq!(lps22.get_lowpass_filter().unwrap(), LowPassFilter::OdrDiv20);
+    assert_eq!(lps22.get_lowpass_cutoff().unwrap(), Some(2.5));
+    lps22.set_lowpass_filter(LowPassFilter::OdrDiv9, false).unwrap();
+    assert_eq!(lps22.get_lowpass_filter().unwrap(), LowPassFilter::OdrDiv9);
+    assert_eq!(LowPassFilter::OdrDiv9.cutoff_hz(ODR::_75Hz), Some(75.0 / 9.0));
+
+    let sim = lps22.destroy();
+    assert_eq!(sim.register(Registers::CTRL_REG1), 0x48);
+}
+
+#[test]
+fn int_source_is_cleared_on_read() {
+    let mut lps22 = sensor(Waveform::Constant(1030.0));
+    lps22.set_threshold(1020).unwrap();
+    lps22
+        .configure_interrupts(InterruptConfig {
+            enable_high_event: FLAG::Enabled,
+            enable_differential: FLAG::Enabled,
+            enable_latch_interrupt: FLAG::Enabled,
+            ..Default::default()
+        })
+        .unwrap();
+    lps22.one_shot().unwrap();
+    let status = lps22.get_int_status().unwrap();
+    assert!(status.interrupt_active && status.diff_press_high && !status.diff_press_low);
+    assert!(!lps22.get_int_status().unwrap().interrupt_active);
+}
+
+#[test]
+fn averaged_measurement() {
+    fn noisy(n: u32) -> f32 {
+        // one outlier among small alternating noise
+        match n {
+            5 => 1020.0,
+            _ => 1000.0 + 0.5 * (n % 2) as f32,
+        }
+    }
+    let mut lps22 = sensor(Waveform::Custom(noisy));
+    let all = lps22
+        .measure_averaged(10, &mut NoDelay, OutlierRejection::Disabled)
+        .unwrap();
+    assert_eq!(all.pressure.count, 10);
+    assert_eq!(all.pressure.max, 1020.0);
+    assert_eq!(all.temperature.mean, 25.0);
+
+    let mut lps22 = sensor(Waveform::Custom(noisy));
+    let filtered = lps22
+        .measure_averaged(10, &mut NoDelay, OutlierRejection::Sigma(2.0))
+        .unwrap();
+    assert_eq!(filtered.pressure.count, 9);
+    assert_eq!(filtered.pressure.min, 1000.0);
+    assert_eq!(filtered.pressure.max, 1000.5);
+    assert!((filtered.pressure.mean - 1000.2222).abs() < 1e-3);
+}
+
+#[test]
+fn storm_alarm_on_pressure_drop() {
+    fn weather(n: u32) -> f32 {
+        if n < 4 {
+            1010.0 - 0.5 * n as f32
+        } else {
+            1004.0
+        }
+    }
+    let mut lps22 = sensor(Waveform::Custom(weather));
+    let mut alarm = StormAlarm::new(StormAlarmConfig {
+        odr: ODR::PowerDown,
+        ..Default::default()
+    });
+    alarm.arm(&mut lps22, 0).unwrap();
+    assert!(alarm.is_armed());
+
+    // reference captured at the first conversion (1010 hPa), drops smaller than 3 hPa
+    for _ in 0..4 {
+        lps22.one_shot().unwrap();
+        assert_eq!(alarm.poll(&mut lps22, 60).unwrap(), StormEvent::Quiet);
+    }
+    // 6 hPa below the reference
+    lps22.one_shot().unwrap();
+    assert_eq!(
+        alarm.poll(&mut lps22, 120).unwrap(),
+        StormEvent::PressureDrop
+    );
+
+    assert_eq!(
+        alarm.poll(&mut lps22, 3 * 3600).unwrap(),
+        StormEvent::ReferenceRefreshed
+    );
+    lps22.one_shot().unwrap();
+    assert_eq!(
+        alarm.poll(&mut lps22, 3 * 3600 + 60).unwrap(),
+        StormEvent::Quiet
+    );
+
+    alarm.disarm(&mut lps22).unwrap();
+    assert_eq!(alarm.poll(&mut lps22, 0).unwrap(), StormEvent::Disarmed);
+}
@@ -0,0 +1,353 @@
+mod common;
+
+use common::SharedSim;
+use lps22hb::average::OutlierRejection;
+use lps22hb::fifo::FIFOConfig;
+use lps22hb::interrupt::InterruptConfig;
+use lps22hb::sensor::Measurement;
+use lps22hb::register::Registers;
+use lps22hb::sim::{SimulatedLps22hb, Waveform};
+use lps22hb::*;
+
+type Sensor = LPS22HB<SimulatedLps22hb>;
+
+fn sensor(pressure: Waveform) -> Sensor {
+    LPS22HB::new(SimulatedLps22hb::with_waveforms(
+        pressure,
+        Waveform::Constant(25.0),
+    ))
+}
+
+/// Let `ms` milliseconds of virtual time pass
+fn wait(lps22: Sensor, ms: u32) -> Sensor {
+    let mut sim = lps22.destroy();
+    sim.advance(ms * 1000);
+    LPS22HB::new(sim)
+}
+
+fn int_drdy(lps22: Sensor) -> (Sensor, bool) {
+    let sim = lps22.destroy();
+    let level = sim.int_drdy();
+    (LPS22HB::new(sim), level)
+}
+
+fn ramp() -> Waveform {
+    Waveform::Ramp {
+        start: 1000.0,
+        step: 1.0,
+    }
+}
+
+#[test]
+fn conversions_follow_the_odr() {
+    let mut lps22 = sensor(ramp());
+    lps22 = wait(lps22, 1000);
+    assert!(!lps22.get_data_status().unwrap().press_available);
+
+    lps22.set_datarate(ODR::_75Hz).unwrap();
+    lps22 = wait(lps22, 13);
+    assert!(!lps22.get_data_status().unwrap().press_available);
+    lps22 = wait(lps22, 1);
+    assert_eq!(lps22.read_pressure().unwrap(), 1000.0);
+
+    // 75 conversions per second, without drift
+    lps22 = wait(lps22, 986);
+    let mut sim = lps22.destroy();
+    assert_eq!(sim.conversions(), 75);
+    assert_eq!(sim.time(), 2_000_000);
+
+    // changing the ODR restarts the timing
+    sim.advance(5000);
+    let mut lps22 = LPS22HB::new(sim);
+    lps22.set_datarate(ODR::_1Hz).unwrap();
+    lps22 = wait(lps22, 999);
+    assert_eq!(lps22.destroy().conversions(), 75);
+}
+
+#[test]
+fn fifo_mode_stops_when_full() {
+    let mut lps22 = sensor(ramp());
+    lps22
+        .configure_fifo(
+            FIFO_ON::Enabled,
+            FIFOConfig {
+                fifo_mode: FIFO_MODE::FIFO,
+                watermark_level: 16,
+                ..Default::default()
+            },
+        )
+        .unwrap();
+    lps22.set_datarate(ODR::_10Hz).unwrap();
+
+    lps22 = wait(lps22, 1600);
+    let status = lps22.get_fifo_status().unwrap();
+    assert_eq!(status.fifo_level, 16);
+    assert!(status.fifo_thresh_reached && !status.fifo_overrun);
+
+    lps22 = wait(lps22, 4000);
+    let status = lps22.get_fifo_status().unwrap();
+    assert_eq!(status.fifo_level, 32);
+    assert!(!status.fifo_overrun);
+
+    let mut samples = [Measurement::default(); 40];
+    assert_eq!(lps22.read_fifo(&mut samples).unwrap(), 32);
+    for (i, sample) in samples[..32].iter().enumerate() {
+        assert_eq!(sample.pressure, 1000.0 + i as f32);
+        assert_eq!(sample.temperature, 25.0);
+    }
+    let status = lps22.get_fifo_status().unwrap();
+    assert!(status.fifo_empty && !status.fifo_thresh_reached);
+}
+
+#[test]
+fn stream_mode_overwrites_oldest_samples() {
+    let mut lps22 = sensor(ramp());
+    lps22
+        .configure_fifo(
+            FIFO_ON::Enabled,
+            FIFOConfig {
+                fifo_mode: FIFO_MODE::Stream,
+                ..Default::default()
+            },
+        )
+        .unwrap();
+    lps22.set_datarate(ODR::_10Hz).unwrap();
+
+    lps22 = wait(lps22, 4000);
+    let status = lps22.get_fifo_status().unwrap();
+    assert_eq!(status.fifo_level, 32);
+    assert!(status.fifo_overrun);
+
+    // the first 8 samples were discarded, reading clears the overrun
+    assert_eq!(lps22.read_measurement().unwrap().pressure, 1008.0);
+    let status = lps22.get_fifo_status().unwrap();
+    assert_eq!(status.fifo_level, 31);
+    assert!(!status.fifo_overrun);
+
+    // Bypass empties the FIFO
+    lps22
+        .configure_fifo(FIFO_ON::Enabled, FIFOConfig::default())
+        .unwrap();
+    assert!(lps22.get_fifo_status().unwrap().fifo_empty);
+}
+
+#[test]
+fn stop_on_watermark_limits_the_depth() {
+    let mut lps22 = sensor(ramp());
+    lps22
+        .configure_fifo(
+            FIFO_ON::Enabled,
+            FIFOConfig {
+                enable_watermark: FLAG::Enabled,
+                fifo_mode: FIFO_MODE::Stream,
+                watermark_level: 8,
+            },
+        )
+        .unwrap();
+    lps22
+        .configure_interrupts(InterruptConfig {
+            active_high_or_low: INT_ACTIVE::Low,
+            enable_fifo_fth: FLAG::Enabled,
+            ..Default::default()
+        })
+        .unwrap();
+    lps22.set_datarate(ODR::_25Hz).unwrap();
+
+    lps22 = wait(lps22, 7 * 40);
+    let (mut lps22, pin) = int_drdy(lps22);
+    assert!(pin);
+    assert_eq!(lps22.get_fifo_status().unwrap().fifo_level, 7);
+
+    lps22 = wait(lps22, 40);
+    let (mut lps22, pin) = int_drdy(lps22);
+    assert!(!pin);
+
+    lps22 = wait(lps22, 10 * 40);
+    let status = lps22.get_fifo_status().unwrap();
+    assert_eq!(status.fifo_level, 8);
+    assert!(status.fifo_thresh_reached && status.fifo_overrun);
+    assert_eq!(lps22.read_measurement().unwrap().pressure, 1010.0);
+}
+
+#[test]
+fn dynamic_stream_mode_keeps_the_watermark_level() {
+    let mut lps22 = sensor(ramp());
+    lps22
+        .configure_fifo(
+            FIFO_ON::Enabled,
+            FIFOConfig {
+                fifo_mode: FIFO_MODE::Dynamic_Stream,
+                watermark_level: 4,
+                ..Default::default()
+            },
+        )
+        .unwrap();
+    lps22.set_datarate(ODR::_10Hz).unwrap();
+
+    // only the 4 newest samples are kept
+    lps22 = wait(lps22, 1000);
+    let status = lps22.get_fifo_status().unwrap();
+    assert_eq!(status.fifo_level, 4);
+    assert!(status.fifo_thresh_reached && status.fifo_overrun);
+    let mut samples = [Measurement::default(); 4];
+    lps22.read_fifo(&mut samples).unwrap();
+    assert_eq!(samples[0].pressure, 1006.0);
+    assert_eq!(samples[3].pressure, 1009.0);
+    assert!(lps22.get_fifo_status().unwrap().fifo_empty);
+
+    // after emptying the FIFO, the first new sample is the first to be read
+    lps22 = wait(lps22, 200);
+    assert_eq!(lps22.get_fifo_status().unwrap().fifo_level, 2);
+    assert_eq!(lps22.read_measurement().unwrap().pressure, 1010.0);
+}
+
+#[test]
+fn data_ready_on_int_drdy() {
+    let mut lps22 = sensor(ramp());
+    lps22
+        .configure_interrupts(InterruptConfig {
+            enable_data_ready: FLAG::Enabled,
+            ..Default::default()
+        })
+        .unwrap();
+    lps22.set_datarate(ODR::_1Hz).unwrap();
+    let (lps22, pin) = int_drdy(lps22);
+    assert!(!pin);
+    let lps22 = wait(lps22, 1000);
+    let (mut lps22, pin) = int_drdy(lps22);
+    assert!(pin);
+    lps22.read_measurement().unwrap();
+    let (_, pin) = int_drdy(lps22);
+    assert!(!pin);
+}
+
+#[test]
+fn autozero_threshold_triggers_stream_to_fifo() {
+    let mut lps22 = sensor(ramp());
+    lps22.set_threshold(40).unwrap();
+    lps22
+        .configure_interrupts(InterruptConfig {
+            data_signal_config: INT_DRDY::P_high,
+            enable_high_event: FLAG::Enabled,
+            enable_differential: FLAG::Enabled,
+            enable_latch_interrupt: FLAG::Enabled,
+            ..Default::default()
+        })
+        .unwrap();
+    lps22.autozero_config(true).unwrap();
+    lps22
+        .configure_fifo(
+            FIFO_ON::Enabled,
+            FIFOConfig {
+                fifo_mode: FIFO_MODE::Stream_to_FIFO,
+                ..Default::default()
+            },
+        )
+        .unwrap();
+    lps22.set_datarate(ODR::_10Hz).unwrap();
+
+    // streaming, reference taken at 1000 hPa, no event up to 1040 hPa
+    lps22 = wait(lps22, 4100);
+    let (mut lps22, pin) = int_drdy(lps22);
+    assert!(!pin);
+    assert!(lps22.get_fifo_status().unwrap().fifo_overrun);
+
+    // the event at 1041 hPa switches to FIFO mode, which stops when full
+    lps22 = wait(lps22, 100);
+    let (mut lps22, pin) = int_drdy(lps22);
+    assert!(pin);
+    lps22 = wait(lps22, 5000);
+    assert_eq!(lps22.get_fifo_status().unwrap().fifo_level, 32);
+    let mut samples = [Measurement::default(); 32];
+    lps22.read_fifo(&mut samples).unwrap();
+    // AUTOZERO output, the last sample is the one that triggered the event
+    assert_eq!(samples[0].pressure, 10.0);
+    assert_eq!(samples[31].pressure, 41.0);
+
+    assert!(lps22.get_int_status().unwrap().diff_press_high);
+    let (_, pin) = int_drdy(lps22);
+    assert!(!pin);
+}
+
+#[test]
+fn autorifp_threshold_triggers_bypass_to_stream() {
+    let mut lps22 = sensor(Waveform::Ramp {
+        start: 1000.0,
+        step: -1.0,
+    });
+    lps22.set_threshold(5).unwrap();
+    lps22
+        .configure_interrupts(InterruptConfig {
+            data_signal_config: INT_DRDY::P_low_or_high,
+            enable_low_event: FLAG::Enabled,
+            enable_differential: FLAG::Enabled,
+            ..Default::default()
+        })
+        .unwrap();
+    lps22.autorifp_config(true).unwrap();
+    lps22
+        .configure_fifo(
+            FIFO_ON::Enabled,
+            FIFOConfig {
+                fifo_mode: FIFO_MODE::Bypass_to_stream,
+                ..Default::default()
+            },
+        )
+        .unwrap();
+    lps22.set_datarate(ODR::_50Hz).unwrap();
+
+    // bypass until the pressure falls more than 5 hPa below the reference
+    lps22 = wait(lps22, 6 * 20);
+    assert!(lps22.get_fifo_status().unwrap().fifo_empty);
+    // AUTORIFP does not change the output registers
+    assert_eq!(lps22.read_pressure().unwrap(), 995.0);
+
+    lps22 = wait(lps22, 20);
+    let (mut lps22, pin) = int_drdy(lps22);
+    assert!(pin);
+    lps22 = wait(lps22, 3 * 20);
+    assert_eq!(lps22.get_fifo_status().unwrap().fifo_level, 4);
+    assert_eq!(lps22.read_measurement().unwrap().pressure, 994.0);
+}
+
+#[test]
+fn averaged_fifo_measurement_restores_the_configuration() {
+    let shared = SharedSim::new(SimulatedLps22hb::with_waveforms(
+        ramp(),
+        Waveform::Constant(25.0),
+    ));
+    let mut lps22 = LPS22HB::new(shared.clone());
+    lps22
+        .configure_fifo(
+            FIFO_ON::Enabled,
+            FIFOConfig {
+                fifo_mode: FIFO_MODE::Stream,
+                watermark_level: 12,
+                ..Default::default()
+            },
+        )
+        .unwrap();
+    lps22.bdu_enable(true).unwrap();
+    lps22.set_datarate(ODR::_1Hz).unwrap();
+    let registers = |shared: &SharedSim| {
+        let sim = shared.0.borrow();
+        [
+            sim.register(Registers::CTRL_REG1),
+            sim.register(Registers::CTRL_REG2),
+            sim.register(Registers::FIFO_CTRL),
+        ]
+    };
+    let before = registers(&shared);
+
+    let result = lps22
+        .measure_averaged_fifo(8, &mut shared.clock(), OutlierRejection::Disabled)
+        .unwrap();
+    assert_eq!(result.pressure.count, 8);
+    assert_eq!(result.pressure.min, 1000.0);
+    assert_eq!(result.pressure.max, 1007.0);
+    // 8 samples at 75 Hz
+    assert!(shared.0.borrow().time() < 120_000);
+
+    assert_eq!(registers(&shared), before);
+    assert_eq!(lps22.get_datarate().unwrap(), Some(ODR::_1Hz));
+}
@@ -0,0 +1,106 @@
+use lps22hb::fifo::FIFOConfig;
+use lps22hb::interrupt::InterruptConfig;
+use lps22hb::preset::Preset;
+use lps22hb::register::Registers;
+use lps22hb::sim::{SimulatedLps22hb, Waveform};
+use lps22hb::snapshot::{ConfigSnapshot, SnapshotError, SNAPSHOT_LEN, SNAPSHOT_VERSION};
+use lps22hb::*;
+
+fn configured() -> LPS22HB<SimulatedLps22hb> {
+    let mut lps22 = LPS22HB::new(SimulatedLps22hb::with_waveforms(
+        Waveform::Constant(1005.0),
+        Waveform::Constant(20.0),
+    ));
+    lps22.apply_preset(Preset::Drone).unwrap();
+    lps22
+        .configure_fifo(
+            FIFO_ON::Enabled,
+            FIFOConfig {
+                fifo_mode: FIFO_MODE::Stream,
+                watermark_level: 16,
+                ..Default::default()
+            },
+        )
+        .unwrap();
+    lps22
+        .configure_interrupts(InterruptConfig {
+            enable_differential: FLAG::Enabled,
+            enable_high_event: FLAG::Enabled,
+            active_high_or_low: INT_ACTIVE::Low,
+            ..Default::default()
+        })
+        .unwrap();
+    lps22.set_threshold(4).unwrap();
+    lps22.set_pressure_offset_raw(-37).unwrap();
+    lps22.set_reference_pressure(1000).unwrap();
+    lps22
+}
+
+#[test]
+fn round_trip_through_bytes() {
+    let mut lps22 = configured();
+    let snapshot = lps22.save_config().unwrap();
+    let bytes = snapshot.to_bytes();
+    assert_eq!(bytes.len(), SNAPSHOT_LEN);
+    assert_eq!(bytes[0], SNAPSHOT_VERSION);
+    assert_eq!(ConfigSnapshot::from_bytes(&bytes), Ok(snapshot));
+    assert_eq!(snapshot.get(Registers::RPDS_L), Some(0xDB));
+    assert_eq!(snapshot.get(Registers::STATUS), None);
+
+    // the self-clearing bits are left out
+    assert_eq!(snapshot.get(Registers::CTRL_REG2).unwrap() & 0x85, 0);
+}
+
+#[test]
+fn power_on_snapshot_encoding() {
+    // version 1, power-on register values (IF_ADD_INC set in CTRL_REG2), CRC-8
+    let expected = [1, 0, 0, 0, 0, 0x10, 0, 0, 0, 0, 0, 0, 0, 0, 0x60];
+    let mut lps22 = LPS22HB::new(SimulatedLps22hb::new());
+    let bytes = lps22.save_config().unwrap().to_bytes();
+    assert_eq!(bytes, expected);
+    assert_eq!(
+        ConfigSnapshot::from_bytes(&expected).unwrap().get(Registers::CTRL_REG2),
+        Some(0x10)
+    );
+}
+
+#[test]
+fn corrupted_or_unknown_snapshots_are_rejected() {
+    let bytes = configured().save_config().unwrap().to_bytes();
+
+    let mut corrupted = bytes;
+    corrupted[5] ^= 0x10;
+    assert_eq!(
+        ConfigSnapshot::from_bytes(&corrupted),
+        Err(SnapshotError::Crc)
+    );
+    assert_eq!(
+        ConfigSnapshot::from_bytes(&[0xFF; SNAPSHOT_LEN]),
+        Err(SnapshotError::Crc)
+    );
+
+    // a valid CRC over another version
+    let other_version = [2, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0xCD];
+    assert_eq!(
+        ConfigSnapshot::from_bytes(&other_version),
+        Err(SnapshotError::Version(2))
+    );
+}
+
+#[test]
+fn settings_survive_a_software_reset() {
+    let mut lps22 = configured();
+    let stored = lps22.save_config().unwrap().to_bytes();
+
+    lps22.software_reset().unwrap();
+    assert_eq!(lps22.read_pressure_offset().unwrap(), 0);
+    assert_eq!(lps22.get_preset().unwrap(), None);
+
+    let snapshot = ConfigSnapshot::from_bytes(&stored).unwrap();
+    lps22.restore_config(&snapshot).unwrap();
+    assert_eq!(lps22.save_config().unwrap(), snapshot);
+    assert_eq!(lps22.read_pressure_offset().unwrap(), -37);
+    assert_eq!(lps22.read_reference_pressure().unwrap(), 1000.0);
+    assert_eq!(lps22.read_threshold().unwrap(), 64);
+    assert_eq!(lps22.get_datarate().unwrap(), Some(ODR::_75Hz));
+}
@@ -0,0 +1,102 @@
+use std::cell::RefCell;
+use std::convert::Infallible;
+use std::rc::Rc;
+
+use embedded_hal::blocking::spi::{Transfer, Write};
+use embedded_hal::digital::v2::OutputPin;
+use lps22hb::interface::SpiInterface;
+use lps22hb::*;
+
+/// Bus activity seen by the mock SPI and chip select pin
+#[derive(Debug, PartialEq)]
+enum Event {
+    Select,
+    Deselect,
+    Bytes(Vec<u8>),
+}
+
+type Log = Rc<RefCell<Vec<Event>>>;
+
+/// Mock SPI logging the bytes it sends and answering reads with `reply`
+struct Spi(Log, Vec<u8>);
+
+impl Write<u8> for Spi {
+    type Error = Infallible;
+
+    fn write(&mut self, words: &[u8]) -> Result<(), Infallible> {
+        self.0.borrow_mut().push(Event::Bytes(words.to_vec()));
+        Ok(())
+    }
+}
+
+impl Transfer<u8> for Spi {
+    type Error = Infallible;
+
+    fn transfer<'w>(&mut self, words: &'w mut [u8]) -> Result<&'w [u8], Infallible> {
+        self.0.borrow_mut().push(Event::Bytes(words.to_vec()));
+        let n = words.len().min(self.1.len());
+        words[..n].copy_from_slice(&self.1[..n]);
+        Ok(words)
+    }
+}
+
+struct ChipSelect(Log);
+
+impl OutputPin for ChipSelect {
+    type Error = Infallible;
+
+    fn set_low(&mut self) -> Result<(), Infallible> {
+        self.0.borrow_mut().push(Event::Select);
+        Ok(())
+    }
+
+    fn set_high(&mut self) -> Result<(), Infallible> {
+        self.0.borrow_mut().push(Event::Deselect);
+        Ok(())
+    }
+}
+
+#[test]
+fn multi_byte_registers_are_written_in_one_frame() {
+    let log = Log::default();
+    let interface = SpiInterface::init(Spi(log.clone(), Vec::new()), ChipSelect(log.clone()));
+    let mut lps22 = LPS22HB::new(interface);
+    lps22.set_reference_pressure(990).unwrap();
+    lps22.set_threshold(10).unwrap();
+
+    // 990 * 4096 = 0x3DE000
+    assert_eq!(
+        *log.borrow(),
+        [
+            Event::Select,
+            Event::Bytes(vec![0x15]),
+            Event::Bytes(vec![0x00, 0xE0, 0x3D]),
+            Event::Deselect,
+            Event::Select,
+            Event::Bytes(vec![0x0C]),
+            Event::Bytes(vec![0xA0, 0x00]),
+            Event::Deselect,
+        ]
+    );
+}
+
+#[test]
+fn multi_byte_registers_are_read_in_one_frame() {
+    let log = Log::default();
+    // 0x3DE000 = 990 * 4096
+    let spi = Spi(log.clone(), vec![0x00, 0xE0, 0x3D]);
+    let interface = SpiInterface::init(spi, ChipSelect(log.clone()));
+    let mut lps22 = LPS22HB::new(interface);
+    assert_eq!(lps22.read_reference_pressure().unwrap(), 990.0);
+
+    // R/W bit set on the address of REF_P_XL, then one dummy byte clocked out per register
+    assert_eq!(
+        *log.borrow(),
+        [
+            Event::Select,
+            Event::Bytes(vec![0x80 | 0x15]),
+            Event::Bytes(vec![0x00, 0x00, 0x00]),
+            Event::Deselect,
+        ]
+    );
+}
@@ -0,0 +1,123 @@
+use lps22hb::sim::{SimulatedLps22hb, Waveform};
+use lps22hb::storm::{StormAlarm, StormAlarmConfig, StormEvent};
+use lps22hb::*;
+
+const HOUR: u32 = 3600;
+
+/// Driver on a simulator returning the given pressures, one per one-shot
+fn sensor(pressure: fn(u32) -> f32) -> LPS22HB<SimulatedLps22hb> {
+    LPS22HB::new(SimulatedLps22hb::with_waveforms(
+        Waveform::Custom(pressure),
+        Waveform::Constant(20.0),
+    ))
+}
+
+/// Alarm on a 3 hPa drop in 3 hours, measuring with one-shots
+fn alarm() -> StormAlarm {
+    StormAlarm::new(StormAlarmConfig {
+        odr: ODR::PowerDown,
+        ..Default::default()
+    })
+}
+
+/// Take a one-shot measurement and poll the alarm
+fn step(lps22: &mut LPS22HB<SimulatedLps22hb>, alarm: &mut StormAlarm, now: u32) -> StormEvent {
+    lps22.one_shot().unwrap();
+    alarm.poll(lps22, now).unwrap()
+}
+
+#[test]
+fn arming_programs_the_threshold() {
+    let mut lps22 = sensor(|_| 1010.0);
+    let mut alarm = alarm();
+    assert_eq!(alarm.poll(&mut lps22, 0).unwrap(), StormEvent::Disarmed);
+    alarm.arm(&mut lps22, 0).unwrap();
+    assert!(alarm.is_armed());
+    assert_eq!(lps22.read_threshold().unwrap(), 3 * 16);
+    assert_eq!(step(&mut lps22, &mut alarm, 60), StormEvent::Quiet);
+    assert_eq!(lps22.read_reference_pressure().unwrap(), 1010.0);
+
+    alarm.disarm(&mut lps22).unwrap();
+    assert!(!alarm.is_armed());
+    assert_eq!(alarm.poll(&mut lps22, 120).unwrap(), StormEvent::Disarmed);
+}
+
+#[test]
+fn fast_drop_triggers_the_alarm() {
+    let mut lps22 = sensor(|n| 1010.0 - 1.0 * n as f32);
+    let mut alarm = alarm();
+    alarm.arm(&mut lps22, 0).unwrap();
+    // exactly 3 hPa below the reference is not enough
+    for n in 1..=4 {
+        assert_eq!(step(&mut lps22, &mut alarm, n * 600), StormEvent::Quiet);
+    }
+    assert_eq!(step(&mut lps22, &mut alarm, 3000), StormEvent::PressureDrop);
+}
+
+#[test]
+fn fractional_drop_uses_the_threshold_resolution() {
+    let mut lps22 = sensor(|n| 1010.0 - 0.5 * n as f32);
+    let mut alarm = StormAlarm::new(StormAlarmConfig {
+        drop: 1.5,
+        odr: ODR::PowerDown,
+        ..Default::default()
+    });
+    alarm.arm(&mut lps22, 0).unwrap();
+    assert_eq!(lps22.read_threshold().unwrap(), 24);
+    // exactly 1.5 hPa below the reference is not enough
+    for n in 1..=4 {
+        assert_eq!(step(&mut lps22, &mut alarm, n * 600), StormEvent::Quiet);
+    }
+    assert_eq!(step(&mut lps22, &mut alarm, 3000), StormEvent::PressureDrop);
+}
+
+#[test]
+fn slow_drift_does_not_trigger_the_alarm() {
+    // 2 hPa per 3 hours, one reading every 30 minutes for two days
+    let mut lps22 = sensor(|n| 1020.0 - n as f32 / 3.0);
+    let mut alarm = alarm();
+    alarm.arm(&mut lps22, 0).unwrap();
+    let mut refreshes = 0;
+    for n in 1..96 {
+        match step(&mut lps22, &mut alarm, n * HOUR / 2) {
+            StormEvent::Quiet => {}
+            StormEvent::ReferenceRefreshed => refreshes += 1,
+            event => panic!("{:?} after {} readings", event, n),
+        }
+    }
+    assert_eq!(refreshes, 31);
+}
+
+#[test]
+fn drop_straddling_a_refresh_triggers_the_alarm() {
+    fn pressure(n: u32) -> f32 {
+        match n {
+            0 => 1010.0,
+            1 => 1008.0,
+            2 => 1008.0,
+            3 => 1007.2,
+            _ => 1006.8,
+        }
+    }
+    let mut lps22 = sensor(pressure);
+    let mut alarm = alarm();
+    alarm.arm(&mut lps22, 0).unwrap();
+    assert_eq!(step(&mut lps22, &mut alarm, HOUR / 2), StormEvent::Quiet);
+    assert_eq!(step(&mut lps22, &mut alarm, HOUR), StormEvent::Quiet);
+
+    // 2 hPa already lost when the reference is refreshed, 1 hPa left
+    assert_eq!(
+        alarm.poll(&mut lps22, 3 * HOUR / 2).unwrap(),
+        StormEvent::ReferenceRefreshed
+    );
+    assert_eq!(lps22.read_threshold().unwrap(), 16);
+    assert_eq!(step(&mut lps22, &mut alarm, 2 * HOUR), StormEvent::Quiet);
+    assert_eq!(lps22.read_reference_pressure().unwrap(), 1008.0);
+    assert_eq!(step(&mut lps22, &mut alarm, 5 * HOUR / 2), StormEvent::Quiet);
+
+    // 3.2 hPa below the pressure 2 hours earlier
+    assert_eq!(
+        step(&mut lps22, &mut alarm, 11 * HOUR / 4),
+        StormEvent::PressureDrop
+    );
+}
@@ -0,0 +1,146 @@
+use lps22hb::interface::tracing::{TraceBuffer, Transaction};
+use lps22hb::interface::TracingInterface;
+use lps22hb::register::Registers;
+use lps22hb::sim::SimulatedLps22hb;
+use lps22hb::*;
+
+#[test]
+fn trace_of_a_driver_call() {
+    let trace = TracingInterface::new(SimulatedLps22hb::new(), TraceBuffer::<8>::new());
+    let mut lps22 = LPS22HB::new(trace);
+    lps22.one_shot().unwrap();
+    lps22.read_measurement().unwrap();
+
+    let (_, buffer) = lps22.destroy().destroy();
+    let log: Vec<Transaction> = buffer.iter().copied().collect();
+    assert_eq!(
+        log,
+        [
+            Transaction::Read { addr: 0x10, len: 1 },
+            Transaction::Write { addr: 0x10, value: 0x00 },
+            Transaction::Read { addr: 0x28, len: 5 },
+            Transaction::Read { addr: 0x11, len: 1 },
+            Transaction::Write { addr: 0x11, value: 0x11 },
+            Transaction::Read { addr: 0x28, len: 5 },
+        ]
+    );
+    assert_eq!(log[5].register(), Some(Registers::PRESS_OUT_XL));
+    assert_eq!(log[4].to_string(), "W CTRL_REG2 (0x11) <- 0x11");
+    assert_eq!(log[5].to_string(), "R PRESS_OUT_XL (0x28) x5");
+    assert_eq!(
+        Transaction::Read { addr: 0x00, len: 1 }.to_string(),
+        "R reserved (0x00) x1"
+    );
+}
+
+#[test]
+fn ring_buffer_keeps_the_latest_transactions() {
+    let mut trace = TracingInterface::new(SimulatedLps22hb::new(), TraceBuffer::<3>::new());
+    for value in 0..5 {
+        lps22hb::interface::Interface::write(&mut trace, 0x0C, value).unwrap();
+    }
+    let buffer = trace.sink();
+    assert_eq!(buffer.len(), 3);
+    assert_eq!(buffer.dropped(), 2);
+    let values: Vec<u8> = buffer
+        .iter()
+        .map(|t| match *t {
+            Transaction::Write { value, .. } => value,
+            _ => unreachable!(),
+        })
+        .collect();
+    assert_eq!(values, [2, 3, 4]);
+
+    trace.sink_mut().clear();
+    assert!(trace.sink().is_empty());
+}
+
+#[test]
+fn callback_sink() {
+    let mut count = 0;
+    {
+        let trace = TracingInterface::new(SimulatedLps22hb::new(), |_: Transaction| count += 1);
+        let mut lps22 = LPS22HB::new(trace);
+        lps22.get_device_id().unwrap();
+        lps22.read_pressure().unwrap();
+    }
+    assert_eq!(count, 2);
+}
+
+#[test]
+fn multi_byte_setters_use_one_transaction() {
+    let trace = TracingInterface::new(SimulatedLps22hb::new(), TraceBuffer::<4>::new());
+    let mut lps22 = LPS22HB::new(trace);
+    lps22.set_threshold(10).unwrap();
+    lps22.set_pressure_offset_raw(-3).unwrap();
+    lps22.set_reference_pressure(1000).unwrap();
+
+    let (sim, buffer) = lps22.destroy().destroy();
+    let log: Vec<Transaction> = buffer.iter().copied().collect();
+    assert_eq!(
+        log,
+        [
+            Transaction::WriteMultiple {
+                addr: 0x0C,
+                len: 2,
+                values: [0xA0, 0x00, 0x00]
+            },
+            Transaction::WriteMultiple {
+                addr: 0x18,
+                len: 2,
+                values: [0xFD, 0xFF, 0x00]
+            },
+            Transaction::WriteMultiple {
+                addr: 0x15,
+                len: 3,
+                values: [0x00, 0x80, 0x3E]
+            },
+        ]
+    );
+    assert_eq!(log[0].values(), [0xA0, 0x00]);
+    assert_eq!(log[0].to_string(), "W THS_P_L (0x0c) <- 0xa0 0x00");
+    assert_eq!(log[1].to_string(), "W RPDS_L (0x18) <- 0xfd 0xff");
+    assert_eq!(log[2].to_string(), "W REF_P_XL (0x15) <- 0x00 0x80 0x3e");
+    assert_eq!(
+        Transaction::write_multiple(0x15, &[1, 2, 3, 4]).to_string(),
+        "W REF_P_XL (0x15) <- 0x01 0x02 0x03 .. x4"
+    );
+    assert_eq!(sim.register(Registers::THS_P_L), 160);
+    assert_eq!(sim.register(Registers::RPDS_H), 0xFF);
+    // 1000 * 4096 = 0x3E8000
+    assert_eq!(sim.register(Registers::REF_P_H), 0x3E);
+    assert_eq!(sim.register(Registers::REF_P_L), 0x80);
+}
+
+#[test]
+fn power_mode_switch_goes_through_power_down() {
+    let mut sim = SimulatedLps22hb::new();
+    sim.set_register(Registers::CTRL_REG1, 0x20);
+    let mut lps22 = LPS22HB::new(TracingInterface::new(sim, TraceBuffer::<8>::new()));
+    lps22.set_power_mode(PowerMode::LowCurrent).unwrap();
+
+    let (_, buffer) = lps22.destroy().destroy();
+    let log: Vec<Transaction> = buffer.iter().copied().collect();
+    assert_eq!(
+        log,
+        [
+            Transaction::Read { addr: 0x10, len: 1 },
+            Transaction::Write { addr: 0x10, value: 0x00 },
+            Transaction::Read { addr: 0x1A, len: 1 },
+            Transaction::Write { addr: 0x1A, value: 0x01 },
+            Transaction::Write { addr: 0x10, value: 0x20 },
+        ]
+    );
+}
+
+#[test]
+fn lowpass_filter_reset_reads_lpfp_res() {
+    let trace = TracingInterface::new(SimulatedLps22hb::new(), TraceBuffer::<4>::new());
+    let mut lps22 = LPS22HB::new(trace);
+    lps22.set_lowpass_filter(LowPassFilter::OdrDiv9, true).unwrap();
+
+    let (_, buffer) = lps22.destroy().destroy();
+    let last = buffer.iter().last().copied().unwrap();
+    assert_eq!(last.register(), Some(Registers::LPFP_RES));
+    assert_eq!(buffer.len(), 3);
+}
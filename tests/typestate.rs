@@ -0,0 +1,131 @@
+mod common;
+
+use common::{SharedSim, StuckOneShot};
+use embedded_hal::blocking::delay::DelayMs;
+use lps22hb::interface::replay::ReplayError;
+use lps22hb::interface::tracing::Transaction;
+use lps22hb::interface::ReplayInterface;
+use lps22hb::register::Registers;
+use lps22hb::sim::{SimulatedLps22hb, Waveform};
+use lps22hb::typestate::{ContinuousRate, ModeChangeError, PowerDown};
+use lps22hb::*;
+
+fn sim() -> SimulatedLps22hb {
+    SimulatedLps22hb::with_waveforms(
+        Waveform::Ramp {
+            start: 1000.0,
+            step: 1.0,
+        },
+        Waveform::Constant(21.5),
+    )
+}
+
+#[test]
+fn one_shot_round_trip() {
+    let mut lps22: LPS22HB<_, PowerDown> = LPS22HB::new(sim()).into_power_down().unwrap();
+    lps22.enable_low_power().unwrap();
+
+    let mut lps22 = lps22.start_one_shot().unwrap();
+    assert!(lps22.is_ready().unwrap());
+    let (lps22, measurement) = lps22.finish().unwrap();
+    assert_eq!(measurement.pressure, 1000.0);
+    assert_eq!(measurement.temperature, 21.5);
+
+    // back in power-down mode, ready for the next one
+    let (lps22, measurement) = lps22.start_one_shot().unwrap().finish().unwrap();
+    assert_eq!(measurement.pressure, 1001.0);
+
+    let sim = lps22.destroy();
+    assert_eq!(sim.conversions(), 2);
+    assert_eq!(sim.register(Registers::RES_CONF), 0x01);
+}
+
+#[test]
+fn one_shot_is_not_finished_before_the_conversion() {
+    let lps22 = LPS22HB::new(StuckOneShot(sim())).into_power_down().unwrap();
+    let mut lps22 = lps22.start_one_shot().unwrap();
+    for _ in 0..3 {
+        match lps22.finish() {
+            Err(ModeChangeError {
+                error: nb::Error::WouldBlock,
+                driver,
+            }) => lps22 = driver,
+            _ => panic!("the conversion never completes"),
+        }
+    }
+    assert!(!lps22.is_ready().unwrap());
+    assert_eq!(lps22.destroy().0.conversions(), 0);
+}
+
+#[test]
+fn one_shot_does_not_return_an_unread_sample() {
+    let sim = SharedSim::new(sim());
+    let lps22 = LPS22HB::new(StuckOneShot(sim.clone())).into_power_down().unwrap();
+    let lps22 = lps22.into_continuous(ContinuousRate::_75Hz).unwrap();
+    // one conversion, left unread
+    sim.clock().delay_ms(20);
+    assert_eq!(sim.0.borrow().conversions(), 1);
+
+    let lps22 = lps22.into_power_down().unwrap();
+    let lps22 = lps22.start_one_shot().unwrap();
+    match lps22.finish() {
+        Err(ModeChangeError {
+            error: nb::Error::WouldBlock,
+            ..
+        }) => {}
+        _ => panic!("the unread sample is not the one-shot measurement"),
+    }
+}
+
+#[test]
+fn continuous_mode() {
+    let lps22 = LPS22HB::new(sim()).into_power_down().unwrap();
+    let mut lps22 = lps22.into_continuous(ContinuousRate::_10Hz).unwrap();
+    lps22.lowpass_filter(true, true).unwrap();
+    lps22.lowpass_filter_reset().unwrap();
+    lps22.set_rate(ContinuousRate::_25Hz).unwrap();
+    assert_eq!(lps22.read_pressure().unwrap(), 0.0);
+
+    let mut sim = lps22.destroy();
+    assert_eq!(sim.register(Registers::CTRL_REG1) & 0xFC, 0x3C);
+    sim.advance(1_000_000);
+    assert_eq!(sim.conversions(), 25);
+
+    // the functions valid in every mode remain available
+    let lps22 = LPS22HB::new(sim).into_power_down().unwrap();
+    let mut lps22 = lps22.into_continuous(ContinuousRate::_1Hz).unwrap();
+    assert_eq!(lps22.read_pressure().unwrap(), 1024.0);
+
+    let lps22 = lps22.into_power_down().unwrap();
+    let sim = lps22.into_untyped().destroy();
+    assert_eq!(sim.register(Registers::CTRL_REG1) & 0x70, 0x00);
+}
+
+#[test]
+fn failed_transition_returns_the_driver() {
+    // the CTRL_REG1 read is recorded, the write is not
+    let recording = [b'R', 0x10, 1, 0x00];
+    let lps22 = LPS22HB::new(ReplayInterface::new(&recording));
+
+    let error = lps22.into_power_down().err().unwrap();
+    assert_eq!(
+        error.error,
+        ReplayError::EndOfRecording(Transaction::Write {
+            addr: 0x10,
+            value: 0x00
+        })
+    );
+    assert!(error.driver.destroy().is_finished());
+}
+
+#[test]
+fn power_mode_outside_one_shot_measurements() {
+    let mut lps22 = LPS22HB::new(sim()).into_power_down().unwrap();
+    lps22.set_power_mode(PowerMode::LowCurrent).unwrap();
+    assert_eq!(lps22.get_power_mode().unwrap(), PowerMode::LowCurrent);
+
+    let mut lps22 = lps22.into_continuous(ContinuousRate::_10Hz).unwrap();
+    lps22.set_power_mode(PowerMode::LowNoise).unwrap();
+    assert_eq!(lps22.get_power_mode().unwrap(), PowerMode::LowNoise);
+    assert_eq!(lps22.get_datarate().unwrap(), Some(ODR::_10Hz));
+}
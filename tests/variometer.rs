@@ -0,0 +1,77 @@
+mod common;
+
+use common::Noise;
+use lps22hb::altitude::{altitude_from_pressure, pressure_at_altitude, STANDARD_PRESSURE};
+use lps22hb::sensor::Measurement;
+use lps22hb::variometer::*;
+use lps22hb::ODR;
+
+/// Synthetic pressure trace at 50 Hz: 10 s on the ground at 500 m, 20 s climbing at 2 m/s, 10 s level
+fn climb_trace() -> Vec<f32> {
+    let mut noise = Noise(1);
+    (0..2000)
+        .map(|i| {
+            let t = i as f32 / 50.0;
+            let altitude = 500.0 + 2.0 * (t - 10.0).clamp(0.0, 20.0);
+            pressure_at_altitude(altitude, STANDARD_PRESSURE) + noise.next()
+        })
+        .collect()
+}
+
+#[test]
+fn altitude_round_trip() {
+    assert!(altitude_from_pressure(STANDARD_PRESSURE, STANDARD_PRESSURE).abs() < 1e-3);
+    // standard atmosphere table: 898.76 hPa at 1000 m
+    assert!((altitude_from_pressure(898.76, STANDARD_PRESSURE) - 1000.0).abs() < 1.0);
+    assert!((pressure_at_altitude(1000.0, STANDARD_PRESSURE) - 898.76).abs() < 0.05);
+}
+
+#[test]
+fn sample_period_from_odr() {
+    assert!(Variometer::new(ODR::PowerDown, VariometerConfig::default()).is_none());
+    let vario = Variometer::new(ODR::_50Hz, VariometerConfig::default()).unwrap();
+    assert!((vario.period() - 0.02).abs() < 1e-6);
+}
+
+#[test]
+fn tracks_climb_rate() {
+    let trace = climb_trace();
+    let mut vario = Variometer::new(ODR::_50Hz, VariometerConfig::default()).unwrap();
+    let outputs: Vec<VarioOutput> = trace.iter().map(|p| vario.update(*p)).collect();
+
+    // on the ground
+    assert!(outputs[450].vertical_speed.abs() < 0.3);
+    assert!((outputs[450].altitude - 500.0).abs() < 1.0);
+    // steady climb
+    for out in &outputs[1000..1450] {
+        assert!((out.vertical_speed - 2.0).abs() < 0.4, "{:?}", out);
+    }
+    // level again
+    assert!(outputs[1999].vertical_speed.abs() < 0.3);
+    assert!((outputs[1999].altitude - 540.0).abs() < 1.0);
+}
+
+#[test]
+fn fifo_bursts_match_single_updates() {
+    let trace = climb_trace();
+    let mut single = Variometer::with_period(0.02, VariometerConfig::default());
+    let mut burst = single.clone();
+
+    for p in &trace {
+        single.update(*p);
+    }
+    let samples: Vec<Measurement> = trace
+        .iter()
+        .map(|p| Measurement {
+            pressure: *p,
+            temperature: 20.0,
+        })
+        .collect();
+    let mut last = None;
+    for chunk in samples.chunks(32) {
+        last = burst.update_burst(chunk);
+    }
+    let (a, b) = (single.output().unwrap(), last.unwrap());
+    assert_eq!(a.altitude, b.altitude);
+    assert_eq!(a.vertical_speed, b.vertical_speed);
+}
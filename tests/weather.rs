@@ -0,0 +1,65 @@
+use lps22hb::altitude::pressure_at_altitude;
+use lps22hb::weather::*;
+
+#[test]
+fn wmo_characteristic() {
+    assert_eq!(Characteristic::from_changes(1.0, 1.0).code(), 2);
+    assert_eq!(Characteristic::from_changes(1.0, 0.0).code(), 1);
+    assert_eq!(Characteristic::from_changes(0.0, 1.0).code(), 3);
+    assert_eq!(Characteristic::from_changes(1.5, -0.5).code(), 0);
+    assert_eq!(Characteristic::from_changes(0.0, 0.05).code(), 4);
+    assert_eq!(Characteristic::from_changes(-1.0, 1.0).code(), 5);
+    assert_eq!(Characteristic::from_changes(-1.0, 0.0).code(), 6);
+    assert_eq!(Characteristic::from_changes(-1.0, -1.0).code(), 7);
+    assert_eq!(Characteristic::from_changes(0.0, -1.0).code(), 8);
+    assert_eq!(Characteristic::from_changes(0.5, -1.5).code(), 8);
+}
+
+#[test]
+fn zambretti_forecast() {
+    assert_eq!(Forecast::zambretti(1040.0, Trend::Steady).letter, 'A');
+    assert_eq!(Forecast::zambretti(1040.0, Trend::Falling).letter, 'B');
+    assert_eq!(Forecast::zambretti(1000.0, Trend::Falling).letter, 'U');
+    assert_eq!(Forecast::zambretti(1013.0, Trend::Steady).letter, 'E');
+    assert_eq!(Forecast::zambretti(990.0, Trend::Rising).letter, 'L');
+    assert_eq!(Forecast::zambretti(940.0, Trend::Falling).letter, 'Z');
+    assert_eq!(
+        Forecast::zambretti(940.0, Trend::Falling).description(),
+        "Stormy, much rain"
+    );
+}
+
+#[test]
+fn tendency_from_history() {
+    let altitude = 250.0;
+    let mut history = PressureHistory::<48>::new(altitude);
+    assert!(history.tendency().is_none());
+
+    // sea-level pressure falling 3 hPa in 3 hours, one reading every 5 minutes
+    let mut last = 0.0;
+    for i in 0..=36u32 {
+        let sea_level = 1015.0 - 3.0 * i as f32 / 36.0;
+        let station = pressure_at_altitude(altitude, sea_level);
+        last = history.add(i * 300, station);
+    }
+    assert!((last - 1012.0).abs() < 0.05);
+
+    let tendency = history.tendency().unwrap();
+    assert!((tendency.change + 3.0).abs() < 0.05);
+    assert_eq!(tendency.trend, Trend::Falling);
+    assert_eq!(tendency.characteristic, Characteristic::Decreasing);
+    assert_eq!(history.forecast().unwrap().letter, 'R');
+}
+
+#[test]
+fn history_wraps_around() {
+    let mut history = PressureHistory::<8>::new(0.0);
+    for i in 0..100u32 {
+        history.add(i * 1800, 1013.0);
+    }
+    assert_eq!(history.len(), 8);
+    let tendency = history.tendency().unwrap();
+    assert!(tendency.change.abs() < 1e-3);
+    assert_eq!(tendency.characteristic, Characteristic::Steady);
+    assert_eq!(tendency.trend, Trend::Steady);
+}